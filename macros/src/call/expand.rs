@@ -9,8 +9,8 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
 
 	// This is a nested vector of all the arguments for each of the functions in `fn_name`. It does
-	// not include the `self` or `caller: T::AccountId` parameter, which we always assume are the
-	// first two parameters to these calls.
+	// not include the `self`, `runtime: &mut T` or `caller: T::AccountId` parameters, which we
+	// always assume are the first parameters to these calls.
 	let args_name = methods
 		.iter()
 		.map(|method| method.args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
@@ -23,38 +23,137 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// This is a vector of the `runtime` argument passed to each of the functions in `fn_name`,
+	// which is empty for the functions which do not take it.
+	let runtime_arg = methods
+		.iter()
+		.map(|method| if method.runtime { quote!(runtime,) } else { quote!() })
+		.collect::<Vec<_>>();
+
+	// This is a vector of the `#[weight(...)]` expression of each of the functions in `fn_name`.
+	let weight = methods.iter().map(|method| &method.weight).collect::<Vec<_>>();
+
+	// This is a vector of the index of each call, used to identify the call when it is encoded.
+	let call_index = (0..methods.len()).map(|i| i as u8).collect::<Vec<_>>();
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
 	// and the `Dispatch` trait logic to route a `caller` to access those functions.
 	let dispatch_impl = quote! {
 		// The callable functions exposed by this pallet.
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
+		//
+		// The hidden `__Ignore` variant uses `T` even when the pallet has no calls, and can never
+		// be created since `Infallible` has no values.
 		#[allow(non_camel_case_types)]
 		pub enum Call<T: Config> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
+			#[doc(hidden)]
+			__Ignore(core::marker::PhantomData<T>, core::convert::Infallible),
+		}
+
+		// The weight of each call is given by the `#[weight(...)]` attribute of its function, which
+		// can use the arguments of the call by reference.
+		impl<T: Config> Call<T> {
+			#[allow(unused_variables)]
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => #weight,
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		// The encoding of a call is the index of the call followed by each of its arguments.
+		// Only available when all of the arguments can be encoded.
+		impl<T: Config> crate::codec::Encode for Call<T>
+		where
+			#( #( #args_type: crate::codec::Encode, )* )*
+		{
+			fn encode_to(&self, dest: &mut Vec<u8>) {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							crate::codec::Encode::encode_to(&#call_index, dest);
+							#( crate::codec::Encode::encode_to(#args_name, dest); )*
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		// Calls can be cloned whenever all of their arguments can.
+		impl<T: Config> Clone for Call<T>
+		where
+			#( #( #args_type: Clone, )* )*
+		{
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => Call::#fn_name {
+							#( #args_name: Clone::clone(#args_name), )*
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		// Decoding reads the index of the call, and then each of its arguments in order.
+		impl<T: Config> crate::codec::Decode for Call<T>
+		where
+			#( #( #args_type: crate::codec::Decode, )* )*
+		{
+			fn decode(input: &mut &[u8]) -> Result<Self, crate::codec::Error> {
+				let index = <u8 as crate::codec::Decode>::decode(input)?;
+				match index {
+					#(
+						#call_index => Ok(Call::#fn_name {
+							#( #args_name: crate::codec::Decode::decode(input)?, )*
+						}),
+					)*
+					_ => Err(crate::codec::Error("invalid call index")),
+				}
+			}
 		}
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
-		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
+		// appropriate function call with all arguments, including the `caller`. Functions which
+		// take the `runtime` argument also get access to the rest of the runtime.
+		//
+		// Functions can return either `DispatchResult` or `DispatchResultWithPostInfo`, which are
+		// both converted into the latter.
+		impl<T: Config> crate::support::PalletDispatch<T> for #pallet_struct<T> {
 			type Caller = T::AccountId;
 			type Call = Call<T>;
 
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
+			#[allow(unused_variables)]
+			fn dispatch(
+				&mut self,
+				runtime: &mut T,
+				caller: Self::Caller,
+				call: Self::Call,
+			) -> crate::support::DispatchResultWithPostInfo {
 				match call {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
 							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
+								#runtime_arg
+								// Note that we assume the first argument of every call after the
+								// optional `runtime` is the `caller`.
 								caller,
 								#( #args_name ),*
-							)?;
+							)
+							.map(Into::into)
 						},
 					)*
+					Call::__Ignore(_, never) => match never {},
 				}
-				Ok(())
 			}
 		}
 	};
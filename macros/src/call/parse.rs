@@ -25,15 +25,23 @@ pub struct CallVariantDef {
 	pub name: syn::Ident,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// Whether the function takes the optional `runtime: &mut T` argument.
+	pub runtime: bool,
+	/// The expression given in the `#[weight(...)]` attribute of the function, which can use the
+	/// args of the function by name.
+	pub weight: syn::Expr,
 }
 
 impl CallDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	/// Parse the callable functions of the `impl` block, removing their `#[weight(...)]`
+	/// attributes from `item`, since they are not real attributes.
+	pub fn try_from(item: &mut syn::Item) -> syn::Result<Self> {
 		// First we check that we are parsing an `impl`.
+		let span = item.span();
 		let item_impl = if let syn::Item::Impl(item) = item {
 			item
 		} else {
-			return Err(syn::Error::new(item.span(), "Invalid pallet::call, expected item impl"))
+			return Err(syn::Error::new(span, "Invalid pallet::call, expected item impl"))
 		};
 
 		// Extract the name of the struct. We mostly assume it is `Pallet`, but we can handle it
@@ -45,8 +53,10 @@ impl CallDef {
 
 		// Here is where we will store all the callable functions.
 		let mut methods = vec![];
-		for item in item_impl.items {
+		for item in item_impl.items.iter_mut() {
 			if let syn::ImplItem::Fn(method) = item {
+				let weight = take_weight_attr(method)?;
+
 				// Here is where we will store all the args for each callable functions.
 				let mut args = vec![];
 
@@ -59,23 +69,32 @@ impl CallDef {
 					},
 				}
 
-				// The second argument should be the `caller: T::AccountId` argument.
-				match method.sig.inputs.iter().skip(1).next() {
+				// The second argument can optionally be `runtime: &mut T`, giving the call access
+				// to the other pallets of the runtime.
+				let runtime = match method.sig.inputs.iter().nth(1) {
+					Some(syn::FnArg::Typed(arg)) => check_runtime_arg(arg)?,
+					_ => false,
+				};
+				// The number of arguments before `caller`.
+				let skip = if runtime { 2 } else { 1 };
+
+				// The next argument should be the `caller: T::AccountId` argument.
+				match method.sig.inputs.iter().nth(skip) {
 					Some(syn::FnArg::Typed(arg)) => {
 						// Here we specifically check that this argument is as we expect for
 						// `caller: T::AccountId`.
 						check_caller_arg(arg)?;
 					},
 					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
+						let msg = "Invalid call, expected `caller: T::AccountId` after `self`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
 				}
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
-				for arg in method.sig.inputs.iter().skip(2) {
+				// Parsing the rest of the args. Skipping `self`, `runtime` and `caller`.
+				for arg in method.sig.inputs.iter().skip(skip + 1) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
 						arg
@@ -96,15 +115,43 @@ impl CallDef {
 				}
 
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				methods.push(CallVariantDef { name: fn_name, args, runtime, weight });
 			}
 		}
 
+		// The index of each call is encoded as a `u8`, so there can be at most 256 of them.
+		if methods.len() > u8::MAX as usize + 1 {
+			let msg = "Invalid pallet::call, too many calls";
+			return Err(syn::Error::new(span, msg))
+		}
+
 		// Return all callable functions for this pallet.
 		Ok(Self { pallet_struct, methods })
 	}
 }
 
+/// Remove the `#[weight(...)]` attribute of a callable function, and return its expression.
+///
+/// Every callable function must have exactly one, so that the weight of every call is known before
+/// it is dispatched.
+pub fn take_weight_attr(method: &mut syn::ImplItemFn) -> syn::Result<syn::Expr> {
+	let (weight, attrs): (Vec<_>, Vec<_>) =
+		method.attrs.drain(..).partition(|attr| attr.path().is_ident("weight"));
+	method.attrs = attrs;
+
+	match weight.as_slice() {
+		[attr] => attr.parse_args::<syn::Expr>(),
+		[] => {
+			let msg = "Invalid call, expected a `#[weight(...)]` attribute";
+			Err(syn::Error::new(method.sig.span(), msg))
+		},
+		[_, attr, ..] => {
+			let msg = "Invalid call, expected a single `#[weight(...)]` attribute";
+			Err(syn::Error::new(attr.span(), msg))
+		},
+	}
+}
+
 /// Check caller arg is exactly: `caller: T::AccountId`.
 ///
 /// This is kept strict to keep the code simple.
@@ -123,7 +170,7 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 	if let syn::Pat::Ident(ident) = &*arg.pat {
 		// We also support the name as `_caller` for when the variable is unused.
 		if &ident.ident != "caller" && &ident.ident != "_caller" {
-			let msg = "Invalid name for second parameter: expected `caller: T::AccountId`";
+			let msg = "Invalid name for caller parameter: expected `caller: T::AccountId`";
 			return Err(syn::Error::new(ident.span(), msg))
 		}
 	}
@@ -131,7 +178,7 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 	// This checks the type is `T::AccountId` with `CheckDispatchableFirstArg`
 	let ty = &arg.ty;
 	syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|e| {
-		let msg = "Invalid type for second parameter: expected `caller: T::AccountId`";
+		let msg = "Invalid type for caller parameter: expected `caller: T::AccountId`";
 		let mut err = syn::Error::new(ty.span(), msg);
 		err.combine(e);
 		err
@@ -139,3 +186,34 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 
 	Ok(())
 }
+
+/// Check whether the arg is the optional `runtime: &mut T` argument.
+///
+/// An argument named `runtime` or `_runtime` must have exactly the type `&mut T`. Any other name is
+/// not the runtime argument, and is checked as the `caller` instead.
+pub fn check_runtime_arg(arg: &syn::PatType) -> syn::Result<bool> {
+	pub struct CheckRuntimeArg;
+	impl syn::parse::Parse for CheckRuntimeArg {
+		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+			input.parse::<syn::Token![&]>()?;
+			input.parse::<syn::Token![mut]>()?;
+			input.parse::<keyword::T>()?;
+			Ok(Self)
+		}
+	}
+
+	match &*arg.pat {
+		syn::Pat::Ident(ident) if ident.ident == "runtime" || ident.ident == "_runtime" => {},
+		_ => return Ok(false),
+	}
+
+	let ty = &arg.ty;
+	syn::parse2::<CheckRuntimeArg>(ty.to_token_stream()).map_err(|e| {
+		let msg = "Invalid type for runtime parameter: expected `runtime: &mut T`";
+		let mut err = syn::Error::new(ty.span(), msg);
+		err.combine(e);
+		err
+	})?;
+
+	Ok(true)
+}
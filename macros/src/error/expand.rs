@@ -0,0 +1,67 @@
+use super::parse::ErrorDef;
+use quote::quote;
+
+/// See the `fn error` docs at the `lib.rs` of this crate for a high level definition.
+pub fn expand_error(def: ErrorDef) -> proc_macro2::TokenStream {
+	let ErrorDef { error_enum, variants } = def;
+
+	// The index of each variant, which is simply the order they are declared in.
+	let index = (0..variants.len()).map(|i| i as u8).collect::<Vec<_>>();
+
+	// A human readable message for each variant, generated from its name.
+	let message = variants.iter().map(|variant| to_sentence(&variant.to_string())).collect::<Vec<_>>();
+
+	quote! {
+		impl #error_enum {
+			// The index of this error inside of the pallet's `Error` enum.
+			pub fn index(&self) -> u8 {
+				match self {
+					#( Self::#variants => #index, )*
+				}
+			}
+
+			// A human readable message describing this error.
+			pub fn message(&self) -> &'static str {
+				match self {
+					#( Self::#variants => #message, )*
+				}
+			}
+		}
+
+		impl core::fmt::Display for #error_enum {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				f.write_str(self.message())
+			}
+		}
+
+		// The pallet index is not known by the pallet itself. It is filled in by the runtime when
+		// the error is returned from a dispatched call, or by the pallet when another pallet calls
+		// it through the runtime.
+		impl From<#error_enum> for crate::support::DispatchError {
+			fn from(error: #error_enum) -> Self {
+				crate::support::DispatchError::Module(crate::support::ModuleError {
+					index: None,
+					error: error.index(),
+					message: error.message(),
+				})
+			}
+		}
+	}
+}
+
+/// Turn a `CamelCase` variant name into a sentence, e.g. `InsufficientBalance` becomes
+/// `Insufficient balance`.
+fn to_sentence(name: &str) -> String {
+	let mut sentence = String::new();
+	for (i, c) in name.chars().enumerate() {
+		if i == 0 {
+			sentence.push(c);
+		} else if c.is_uppercase() {
+			sentence.push(' ');
+			sentence.extend(c.to_lowercase());
+		} else {
+			sentence.push(c);
+		}
+	}
+	sentence
+}
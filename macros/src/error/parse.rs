@@ -0,0 +1,53 @@
+use syn::spanned::Spanned;
+
+/// This object will collect all the information we need to keep while parsing the `Error` enum of
+/// a pallet.
+#[derive(Debug)]
+pub struct ErrorDef {
+	/// This is the name of the enum used by the pallet. We mostly assume it is `Error`.
+	pub error_enum: syn::Ident,
+	/// This is the list of the variants of the enum, in the order they are declared.
+	pub variants: Vec<syn::Ident>,
+}
+
+impl ErrorDef {
+	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+		// First we check that we are parsing an `enum`.
+		let item_enum = if let syn::Item::Enum(item) = item {
+			item
+		} else {
+			return Err(syn::Error::new(item.span(), "Invalid pallet::error, expected item enum"))
+		};
+
+		// Generics are not supported, since the error is converted into a `DispatchError` which
+		// only keeps the index and the message of the variant.
+		if !item_enum.generics.params.is_empty() {
+			let msg = "Invalid pallet::error, generics are not supported";
+			return Err(syn::Error::new(item_enum.generics.span(), msg))
+		}
+
+		// The index of each variant is stored as a `u8` in the `DispatchError`, so there can be at
+		// most 256 of them.
+		if item_enum.variants.len() > u8::MAX as usize + 1 {
+			let msg = "Invalid pallet::error, too many variants";
+			return Err(syn::Error::new(item_enum.span(), msg))
+		}
+
+		// Here is where we will store the name of all the variants.
+		let mut variants = vec![];
+		for variant in item_enum.variants {
+			// Variants can't hold any data, and we use the order of declaration as their index.
+			if !matches!(variant.fields, syn::Fields::Unit) {
+				let msg = "Invalid pallet::error, variants must not have fields";
+				return Err(syn::Error::new(variant.fields.span(), msg))
+			}
+			if variant.discriminant.is_some() {
+				let msg = "Invalid pallet::error, variants must not have explicit discriminants";
+				return Err(syn::Error::new(variant.span(), msg))
+			}
+			variants.push(variant.ident);
+		}
+
+		Ok(Self { error_enum: item_enum.ident, variants })
+	}
+}
@@ -1,6 +1,27 @@
 mod call;
+mod error;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// This generates an `enum Call<T>` with one variant for each function, and implements
+/// `support::PalletDispatch` on the pallet to route each variant to its function.
+///
+/// Every function takes `&mut self` and `caller: T::AccountId` first. A function can also take
+/// `runtime: &mut T` right after `self`, to access the other pallets of the runtime through
+/// `support::HasPallet`. The remaining arguments become the fields of its variant. The `Call` enum
+/// implements `codec::Encode` and `codec::Decode` whenever all of its arguments do, where each
+/// variant is identified by the order in which the functions are declared. The index is encoded as
+/// a `u8`, so a pallet can have at most 256 calls.
+///
+/// Every function must have a `#[weight(...)]` attribute, with an expression of type
+/// `support::Weight` for the work done by the call. The expression can use the arguments of the
+/// function by reference, so the weight can depend on them. It becomes `Call::weight()`, and the
+/// attribute itself is removed. A function returns either `support::DispatchResult`, or
+/// `support::DispatchResultWithPostInfo` to report that it used less than its declared weight.
+///
+/// A pallet without calls still uses the macro on an empty `impl`, since the runtime needs its
+/// `Call` enum.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -9,22 +30,71 @@ pub fn call(
 	call::call(attr, item)
 }
 
+/// Expand the `Error` enum of a pallet.
+///
+/// The enum must only contain unit variants. This generates:
+/// - `fn index()` - the position of the variant in the enum, used as the error code.
+/// - `fn message()` - a human readable message, generated from the name of the variant. For
+///   example `InsufficientBalance` becomes `"Insufficient balance"`.
+/// - implements `Display` using that message.
+/// - implements `From<Error>` for `support::DispatchError`, so pallet calls can use `?` on their
+///   own errors. The index of the pallet is filled in by the runtime when dispatching.
+#[proc_macro_attribute]
+pub fn error(
+	attr: proc_macro::TokenStream,
+	item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+	error::error(attr, item)
+}
+
 /// Expand the `Runtime` definition.
 ///
 /// This generates function implementations on `Runtime`:
-/// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
-///   included in the runtime.
+/// - `fn from_genesis()` - which generates a new instance of the runtime, by building all the
+///   pallets included in the runtime from their `GenesisConfig`. Each pallet must expose a
+///   `GenesisConfig<T>` struct and a `from_genesis()` function. The hash of the genesis block
+///   commits to the state root of the initial state.
+/// - `fn genesis_config()` - which exports the current state as a `RuntimeGenesisConfig`, using the
+///   `genesis_config()` function of every pallet.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
 ///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   valid block number, extends the last imported block, commits to its extrinsics and declares
+///   the resulting state root. Blocks whose total weight, from the `on_initialize` hooks and the
+///   `weight()` of every call, exceeds `system::Config::MAX_BLOCK_WEIGHT` are rejected. Extrinsics
+///   whose signature or nonce is invalid, or whose fee can not be paid through
+///   `types::ChargeTransaction`, are rejected without being dispatched. It returns a
+///   `types::BlockReceipt`, with a `support::ExtrinsicReceipt` for every extrinsic: the result of
+///   its call, the weight it used, the fee it paid and the events it emitted. Every extrinsic, and
+///   the block as a whole, is executed inside a storage transaction, so the `Runtime` must
+///   implement `Clone`. Each pallet must implement `support::Hooks`: `on_initialize` runs before
+///   the extrinsics, and `on_idle` and then `on_finalize` after them, each hook in the order the
+///   pallets are declared. Execution panics if an `on_idle` uses more than the weight left.
+/// - `fn build_block()` - which builds the next block from a list of extrinsics, executing them on
+///   a copy of the runtime to find the state root.
+/// - `fn state_root()` - the merkle root of the storage of all pallets. Each pallet must expose a
+///   `storage_entries()` function returning its encoded storage.
+/// - `fn storage_proof()` - which proves the value stored at a key against the state root, so it
+///   can be checked with `support::StorageProof::verify` without access to the whole state.
+///
+/// It also generates `struct RuntimeGenesisConfig`, with the `GenesisConfig` of every pallet
+/// (including system) in a field named after the pallet.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
 /// - Note: For simplicity, we assume that the system pallet is not callable.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
-///   all pallets. The system pallet is not included.
+///   all pallets. The system pallet is not included. Its `weight()` is the weight of the pallet
+///   level call. It implements `codec::Encode` and
+///   `codec::Decode`, where a call is identified by the index of its pallet (starting at 1, since
+///   system is index 0) followed by the encoding of the pallet level `Call`.
+/// - `enum RuntimeEvent` - an "outer"-enum representing the accumulation of all the events emitted
+///   by all pallets. Each pallet must expose an `Event<T>` enum and a `take_events()` function. After
+///   every extrinsic, the pending events of each pallet are deposited in the system pallet.
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
 ///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+///   pallet is not included. Each pallet must expose a `new()` function, since the pallet is taken
+///   out of the runtime while its call executes, so the call can be given the rest of the runtime.
+/// - implements the trait `support::HasPallet` for every pallet, including system, which is how
+///   pallets access each other.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
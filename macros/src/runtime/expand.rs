@@ -7,40 +7,310 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 
 	// This is a vector of all the pallet names, not including system.
 	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	// This is a vector of the index of each pallet in the runtime. The system pallet is index 0.
+	let pallet_index = (1..=pallets.len()).map(|i| i as u8).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
 	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
 		impl #runtime_struct {
-			// Create a new instance of the main Runtime, by creating a new instance of each pallet.
-			fn new() -> Self {
-				Self {
+			// Create a new instance of the main Runtime, by building each pallet from its genesis
+			// config. The genesis block hash commits to the resulting state.
+			fn from_genesis(config: RuntimeGenesisConfig) -> Self {
+				let mut runtime = Self {
 					// Since system is not included in the list of pallets, we manually add it here.
-					system: <system::Pallet::<Self>>::new(),
+					system: <system::Pallet::<Self>>::from_genesis(config.system),
 					#(
-						#pallet_names: <#pallet_types>::new()
+						#pallet_names: <#pallet_types>::from_genesis(config.#pallet_names)
 					),*
-				}
+				};
+				let state_root = runtime.state_root();
+				runtime.system.initialize_genesis(state_root);
+				runtime
 			}
 
-			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
-				self.system.inc_block_number();
-				if block.header.block_number != self.system.block_number() {
-					return Err(&"block number does not match what is expected")
+			// Export the current state of every pallet as a genesis config, which can be used to
+			// start a new chain from this state.
+			fn genesis_config(&self) -> RuntimeGenesisConfig {
+				RuntimeGenesisConfig {
+					system: self.system.genesis_config(),
+					#( #pallet_names: self.#pallet_names.genesis_config(), )*
 				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
-					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
-					});
+			}
+
+			// Check that the extrinsic is signed by its caller, over the payload described by
+			// `support::Extrinsic::signing_payload`. Its birth block must be one of the recent
+			// blocks whose hash is still kept.
+			fn check_signature(&self, extrinsic: &types::Extrinsic) -> crate::support::DispatchResult {
+				let signature = extrinsic
+					.signature
+					.as_ref()
+					.ok_or(crate::support::InvalidTransaction::Unsigned)?;
+				let birth_hash = self
+					.system
+					.block_hash(extrinsic.birth)
+					.ok_or(crate::support::InvalidTransaction::UnknownBirth)?;
+				let payload = extrinsic.signing_payload(&birth_hash);
+				if !crate::support::Verify::verify(signature, &payload, &extrinsic.caller) {
+					return Err(crate::support::InvalidTransaction::BadSignature.into())
 				}
 				Ok(())
 			}
+
+			// Execute a block of extrinsics. Increments the block number. Returns the receipt of
+			// the block, with the result of every extrinsic in it.
+			//
+			// The header must extend the last imported block, commit to the extrinsics in the body,
+			// and declare the state root reached after executing them. The whole block is executed
+			// inside a storage transaction, so an invalid block leaves no changes behind.
+			fn execute_block(
+				&mut self,
+				block: types::Block,
+			) -> Result<types::BlockReceipt, crate::support::DispatchError> {
+				crate::support::with_transaction(self, |runtime| runtime.apply_block(block))
+			}
+
+			// The logic of `execute_block`, without the outer storage transaction.
+			fn apply_block(
+				&mut self,
+				block: types::Block,
+			) -> Result<types::BlockReceipt, crate::support::DispatchError> {
+				let types::Block { header, extrinsics } = block;
+				if header.parent_hash != self.system.last_block_hash() {
+					return Err(crate::support::DispatchError::Other(
+						"parent hash does not match the last imported block",
+					))
+				}
+				if header.extrinsics_root != types::Block::extrinsics_root(&extrinsics) {
+					return Err(crate::support::DispatchError::Other(
+						"extrinsics root does not match the extrinsics in the block",
+					))
+				}
+				let weight = self.initialize_block();
+				if header.block_number != self.system.block_number() {
+					return Err(crate::support::DispatchError::Other(
+						"block number does not match what is expected",
+					))
+				}
+				// Every extrinsic in the block counts towards its weight, even if it fails.
+				let weight = weight.saturating_add(Self::extrinsics_weight(&extrinsics));
+				if weight > <#runtime_struct as system::Config>::MAX_BLOCK_WEIGHT {
+					return Err(crate::support::DispatchError::Other(
+						"block weight exceeds the limit",
+					))
+				}
+				let receipts = self.apply_extrinsics(extrinsics);
+				self.finalize_block(weight);
+				if header.state_root != self.state_root() {
+					return Err(crate::support::DispatchError::Other(
+						"state root does not match the state after executing the block",
+					))
+				}
+				self.system.note_block_hash(header.block_number, header.hash());
+				Ok(crate::support::BlockReceipt {
+					block_number: header.block_number,
+					extrinsics: receipts,
+				})
+			}
+
+			// Build the next block on top of the last imported block, containing `extrinsics`.
+			//
+			// The extrinsics are executed on a copy of the runtime to find the state root, so the
+			// runtime itself is left unchanged until the block is imported with `execute_block`.
+			// The block is built even if it is too heavy, and then rejected by `execute_block`.
+			fn build_block(&self, extrinsics: Vec<types::Extrinsic>) -> types::Block {
+				let mut runtime = self.clone();
+				let parent_hash = runtime.system.last_block_hash();
+				let extrinsics_root = types::Block::extrinsics_root(&extrinsics);
+				let weight = runtime.initialize_block();
+				let weight = weight.saturating_add(Self::extrinsics_weight(&extrinsics));
+				let block_number = runtime.system.block_number();
+				runtime.apply_extrinsics(extrinsics.clone());
+				runtime.finalize_block(weight);
+				let header = crate::support::Header {
+					parent_hash,
+					block_number,
+					state_root: runtime.state_root(),
+					extrinsics_root,
+				};
+				types::Block { header, extrinsics }
+			}
+
+			// Start a new block. Increments the block number, and runs the
+			// `support::Hooks::on_initialize` hook of every pallet, in the order they are declared in
+			// the runtime. Returns the weight consumed by the hooks.
+			fn initialize_block(&mut self) -> crate::support::Weight {
+				// Events are only kept for the block currently being executed.
+				self.system.reset_events();
+				self.system.inc_block_number();
+				let block_number = self.system.block_number();
+				let mut weight: crate::support::Weight = 0;
+				#(
+					// Like a call, the hook can access every other pallet through the runtime.
+					self.system.take_pallet(stringify!(#pallet_names));
+					let mut pallet =
+						std::mem::replace(&mut self.#pallet_names, <#pallet_types>::new());
+					weight = weight.saturating_add(
+						crate::support::Hooks::on_initialize(&mut pallet, self, block_number),
+					);
+					self.#pallet_names = pallet;
+					self.system.restore_pallet(stringify!(#pallet_names));
+				)*
+				self.deposit_pallet_events();
+				weight
+			}
+
+			// The total weight of the calls of `extrinsics`.
+			fn extrinsics_weight(extrinsics: &[types::Extrinsic]) -> crate::support::Weight {
+				extrinsics
+					.iter()
+					.fold(0, |weight, extrinsic| weight.saturating_add(extrinsic.call.weight()))
+			}
+
+			// Apply the extrinsics of a block, in order. Returns the receipt of each extrinsic.
+			//
+			// Extrinsics which are not signed by their caller, whose nonce does not match the
+			// current nonce of the caller, or whose caller can not pay the fee, are rejected without
+			// being dispatched. This protects against forged and replayed extrinsics.
+			//
+			// Each extrinsic is dispatched inside its own storage transaction, so a call which
+			// returns an error leaves no changes behind in any pallet. The nonce is incremented and
+			// the fee is charged outside of the transaction, so they are kept even when the call
+			// fails.
+			fn apply_extrinsics(
+				&mut self,
+				extrinsics: Vec<types::Extrinsic>,
+			) -> Vec<types::ExtrinsicReceipt> {
+				extrinsics.into_iter().map(|extrinsic| self.apply_extrinsic(extrinsic)).collect()
+			}
+
+			// Apply a single extrinsic, as described in `apply_extrinsics`. The receipt has the
+			// events deposited by the extrinsic, which are also kept in the system pallet until the
+			// end of the block.
+			fn apply_extrinsic(&mut self, extrinsic: types::Extrinsic) -> types::ExtrinsicReceipt {
+				let first_event = self.system.events().len();
+				let (result, weight, fee) = match self.charge_extrinsic(&extrinsic) {
+					Ok(paid) => self.dispatch_extrinsic(extrinsic, paid),
+					Err(e) => (Err(e), 0, Default::default()),
+				};
+				// A failed call has already been rolled back, so it has no events left.
+				self.deposit_pallet_events();
+				crate::support::ExtrinsicReceipt {
+					result,
+					weight,
+					fee,
+					events: self.system.events()[first_event..].to_vec(),
+				}
+			}
+
+			// Check that the extrinsic can be dispatched, and charge the fee for its weight and
+			// encoded length through `types::ChargeTransaction`. Returns the fee charged.
+			fn charge_extrinsic(
+				&mut self,
+				extrinsic: &types::Extrinsic,
+			) -> Result<types::Balance, crate::support::DispatchError> {
+				self.check_signature(extrinsic)?;
+				self.system.check_birth(&extrinsic.caller, extrinsic.birth)?;
+				self.system.check_nonce(&extrinsic.caller, extrinsic.nonce)?;
+				let length = crate::codec::Encode::encode(extrinsic).len();
+				<types::ChargeTransaction as crate::support::ChargeTransaction<Self>>::withdraw_fee(
+					self,
+					&extrinsic.caller,
+					extrinsic.call.weight(),
+					length,
+					extrinsic.tip,
+				)
+			}
+
+			// Dispatch an extrinsic whose fee `paid` has already been charged. Afterwards, the fee
+			// for the weight the call did not use is refunded. A failed call uses all of its weight.
+			// Returns the result of the call, the weight it used and the fee paid in the end.
+			fn dispatch_extrinsic(
+				&mut self,
+				extrinsic: types::Extrinsic,
+				paid: types::Balance,
+			) -> (crate::support::DispatchResult, crate::support::Weight, types::Balance) {
+				let crate::support::Extrinsic { caller, call, tip, .. } = extrinsic;
+				let weight = call.weight();
+				self.system.inc_nonce(&caller);
+				let result = crate::support::with_transaction(self, |runtime| {
+					crate::support::Dispatch::dispatch(runtime, caller.clone(), call)
+				});
+				let actual_weight = match &result {
+					Ok(info) => info.actual_weight.map_or(weight, |actual| actual.min(weight)),
+					Err(_) => weight,
+				};
+				let fee = <types::ChargeTransaction as crate::support::ChargeTransaction<Self>>::
+					correct_fee(self, &caller, weight, actual_weight, paid, tip);
+				(result.map(|_| ()), actual_weight, fee)
+			}
+
+			// Finish the current block, after its extrinsics, where `weight` has already been
+			// consumed. Runs the `support::Hooks::on_idle` hook and then the
+			// `support::Hooks::on_finalize` hook of every pallet, in the order they are declared in
+			// the runtime.
+			//
+			// `on_idle` can use whatever `weight` left of `system::Config::MAX_BLOCK_WEIGHT`, and
+			// panics if it uses more, since the block would then exceed the limit.
+			fn finalize_block(&mut self, mut weight: crate::support::Weight) {
+				let block_number = self.system.block_number();
+				#(
+					self.system.take_pallet(stringify!(#pallet_names));
+					let mut pallet =
+						std::mem::replace(&mut self.#pallet_names, <#pallet_types>::new());
+					let remaining_weight =
+						<#runtime_struct as system::Config>::MAX_BLOCK_WEIGHT.saturating_sub(weight);
+					let used = crate::support::Hooks::on_idle(&mut pallet, self, remaining_weight);
+					assert!(
+						used <= remaining_weight,
+						"`on_idle` of `{}` used more than the remaining weight",
+						stringify!(#pallet_names),
+					);
+					weight = weight.saturating_add(used);
+					self.#pallet_names = pallet;
+					self.system.restore_pallet(stringify!(#pallet_names));
+				)*
+				#(
+					self.system.take_pallet(stringify!(#pallet_names));
+					let mut pallet =
+						std::mem::replace(&mut self.#pallet_names, <#pallet_types>::new());
+					crate::support::Hooks::on_finalize(&mut pallet, self, block_number);
+					self.#pallet_names = pallet;
+					self.system.restore_pallet(stringify!(#pallet_names));
+				)*
+				self.deposit_pallet_events();
+			}
+
+			// Move the events emitted by the pallets since the last call into the system pallet.
+			fn deposit_pallet_events(&mut self) {
+				#(
+					for event in self.#pallet_names.take_events() {
+						self.system.deposit_event(RuntimeEvent::#pallet_names(event));
+					}
+				)*
+			}
+
+			// The storage of every pallet. Entries are keyed by the name of their pallet in the
+			// runtime, as described by `support::prefixed_key`.
+			fn storage(&self) -> std::collections::BTreeMap<Vec<u8>, Vec<u8>> {
+				crate::support::state_entries([
+					("system", self.system.storage_entries()),
+					#( (stringify!(#pallet_names), self.#pallet_names.storage_entries()), )*
+				])
+			}
+
+			// The root of the storage of every pallet, which the header of a block must declare
+			// in `state_root`.
+			fn state_root(&self) -> [u8; 32] {
+				crate::support::state_root(&self.storage())
+			}
+
+			// Prove the value stored at `key` against the current state root, which is the state
+			// root of the last imported block. Returns `None` if there is nothing stored at `key`.
+			fn storage_proof(&self, key: &[u8]) -> Option<crate::support::StorageProof> {
+				crate::support::StorageProof::generate(&self.storage(), key)
+			}
 		}
 	};
 
@@ -51,10 +321,64 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Clone)]
 		pub enum RuntimeCall {
 			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
 		}
 
+		// The genesis config of every pallet, used to build the initial state of the runtime.
+		#[derive(Debug, Clone, Default)]
+		pub struct RuntimeGenesisConfig {
+			pub system: system::GenesisConfig<#runtime_struct>,
+			#( pub #pallet_names: #pallet_names::GenesisConfig<#runtime_struct>, )*
+		}
+
+		// These are all the events which can be emitted by the pallets.
+		// Like `RuntimeCall`, it is just an accumulation of the events of each pallet.
+		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone)]
+		pub enum RuntimeEvent {
+			#( #pallet_names(#pallet_names::Event<#runtime_struct>) ),*
+		}
+
+		impl RuntimeCall {
+			// The weight of the call, given by the `#[weight(...)]` attribute of its function.
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#( RuntimeCall::#pallet_names(call) => call.weight(), )*
+				}
+			}
+		}
+
+		// The encoding of a call is the index of the pallet followed by the encoding of the call
+		// inside of that pallet.
+		impl crate::codec::Encode for RuntimeCall {
+			fn encode_to(&self, dest: &mut Vec<u8>) {
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => {
+							crate::codec::Encode::encode_to(&#pallet_index, dest);
+							crate::codec::Encode::encode_to(call, dest);
+						}
+					),*
+				}
+			}
+		}
+
+		impl crate::codec::Decode for RuntimeCall {
+			fn decode(input: &mut &[u8]) -> Result<Self, crate::codec::Error> {
+				let index = <u8 as crate::codec::Decode>::decode(input)?;
+				match index {
+					#(
+						#pallet_index => Ok(RuntimeCall::#pallet_names(
+							crate::codec::Decode::decode(input)?
+						)),
+					)*
+					_ => Err(crate::codec::Error("invalid pallet index")),
+				}
+			}
+		}
+
 		impl crate::support::Dispatch for #runtime_struct {
 			type Caller = <Runtime as system::Config>::AccountId;
 			type Call = RuntimeCall;
@@ -67,25 +391,74 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				&mut self,
 				caller: Self::Caller,
 				runtime_call: Self::Call,
-			) -> crate::support::DispatchResult {
+			) -> crate::support::DispatchResultWithPostInfo {
 				// This match statement will allow us to correctly route `RuntimeCall`s
 				// to the appropriate pallet level call.
 				match runtime_call {
 					#(
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							// The pallet is taken out of the runtime while its call executes,
+							// so the call can access every other pallet through the runtime.
+							// Accessing the pallet itself through the runtime panics until it
+							// is put back.
+							self.system.take_pallet(stringify!(#pallet_names));
+							let mut pallet =
+								std::mem::replace(&mut self.#pallet_names, <#pallet_types>::new());
+							let result = crate::support::PalletDispatch::dispatch(
+								&mut pallet,
+								self,
+								caller,
+								call,
+							);
+							self.#pallet_names = pallet;
+							self.system.restore_pallet(stringify!(#pallet_names));
+							result.map_err(|e| e.with_pallet_index(#pallet_index))
 						}
 					),*
 				}
-				Ok(())
 			}
 		}
 	};
 
+	// This quote block gives access to every pallet from the runtime, which is how pallets access
+	// each other.
+	let has_pallet_impl = quote! {
+		impl crate::support::HasPallet<system::Pallet<#runtime_struct>> for #runtime_struct {
+			const INDEX: u8 = 0;
+
+			fn pallet(&self) -> &system::Pallet<#runtime_struct> {
+				&self.system
+			}
+
+			fn pallet_mut(&mut self) -> &mut system::Pallet<#runtime_struct> {
+				&mut self.system
+			}
+		}
+
+		#(
+			impl crate::support::HasPallet<#pallet_names::Pallet<#runtime_struct>>
+				for #runtime_struct
+			{
+				const INDEX: u8 = #pallet_index;
+
+				fn pallet(&self) -> &#pallet_names::Pallet<#runtime_struct> {
+					self.system.ensure_not_taken(stringify!(#pallet_names));
+					&self.#pallet_names
+				}
+
+				fn pallet_mut(&mut self) -> &mut #pallet_names::Pallet<#runtime_struct> {
+					self.system.ensure_not_taken(stringify!(#pallet_names));
+					&mut self.#pallet_names
+				}
+			}
+		)*
+	};
+
 	// We combine and return all the generated code.
 	quote! {
 		#dispatch_impl
 		#runtime_impl
+		#has_pallet_impl
 	}
 	.into()
 }
@@ -33,6 +33,13 @@ impl RuntimeDef {
 			}
 		}
 
+		// The index of each pallet is a `u8`, and index 0 belongs to `system`, so there can be at
+		// most 255 other pallets.
+		if pallets.len() > u8::MAX as usize {
+			let msg = "runtime struct can have at most 255 pallets besides system";
+			return Err(syn::Error::new(runtime_struct.span(), msg))
+		}
+
 		Ok(Self { runtime_struct, pallets })
 	}
 }
@@ -1,105 +1,1331 @@
+use crate::codec::Encode;
+use crate::support::{
+    storage_key, ArithmeticError, BalanceStatus, Currency, DispatchError, DispatchResult,
+    DispatchResultWithPostInfo, ExistenceRequirement, HasPallet, Hooks, PostDispatchInfo,
+    ReservableCurrency, StorageEntry, WithdrawReason,
+};
+use core::fmt::Debug;
 use num::traits::{CheckedAdd, CheckedSub, Zero};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-pub trait Config: crate::system::Config {
-    type Balance: CheckedAdd + CheckedSub + Zero + Copy;
+pub trait Config: crate::system::Config + HasPallet<crate::system::Pallet<Self>> + Sized {
+    /// O tipo que representa o saldo de uma conta.
+    type Balance: CheckedAdd + CheckedSub + Zero + Copy + PartialOrd + Debug + Encode;
+    /// O saldo mínimo de uma conta. Contas que ficam com menos do que isso depois de uma
+    /// transferência são removidas, então nenhuma conta é removida se este valor for zero.
+    const EXISTENTIAL_DEPOSIT: Self::Balance;
 }
 
-#[derive(Debug)]
+/// Os erros que as chamadas e as moedas deste pallet podem retornar.
+#[macros::error]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// O saldo livre da conta não é suficiente.
+    InsufficientBalance,
+    /// A conta ficaria com um saldo menor que `EXISTENTIAL_DEPOSIT`.
+    BelowExistentialDeposit,
+    /// A conta seria removida, mas isso não foi permitido ou ela tem fundos reservados ou
+    /// bloqueios.
+    WouldReapAccount,
+    /// Os bloqueios da conta não permitem usar esses fundos.
+    LiquidityRestrictions,
+    /// A conta não existe.
+    DeadAccount,
+}
+
+/// O nome de um bloqueio, para que o pallet que o criou possa alterá-lo ou removê-lo depois.
+pub type LockIdentifier = [u8; 8];
+
+/// Para que os fundos bloqueados não podem ser usados.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reasons {
+    /// Pagar taxas de transação.
+    Fee,
+    /// Todo o resto, como transferências e reservas.
+    Misc,
+    /// Os dois acima.
+    All,
+}
+
+impl Reasons {
+    fn intersects(self, other: Reasons) -> bool {
+        self == Reasons::All || other == Reasons::All || self == other
+    }
+}
+
+impl From<WithdrawReason> for Reasons {
+    fn from(reason: WithdrawReason) -> Self {
+        match reason {
+            WithdrawReason::TransactionPayment => Reasons::Fee,
+            WithdrawReason::Other => Reasons::Misc,
+        }
+    }
+}
+
+impl Encode for Reasons {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (*self as u8).encode_to(dest);
+    }
+}
+
+/// Impede que pelo menos `amount` do saldo livre de uma conta seja usado para `reasons`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceLock<Balance> {
+    /// O nome do bloqueio.
+    pub id: LockIdentifier,
+    /// O saldo livre abaixo do qual a conta não pode ir.
+    pub amount: Balance,
+    /// Para que os fundos bloqueados não podem ser usados.
+    pub reasons: Reasons,
+}
+
+impl<Balance: Encode> Encode for BalanceLock<Balance> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (&self.id, &self.amount, &self.reasons).encode_to(dest);
+    }
+}
+
+/// Os eventos emitidos por este pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// `amount` passou de `from` para `to`.
+    Transfer {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` novos fundos foram criados na conta de `who`.
+    Minted {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` foi destruído da conta de `who`.
+    Burned {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// A conta de `who` foi criada.
+    NewAccount { who: T::AccountId },
+    /// A conta foi removida, e a poeira (`dust`) que sobrou nela foi destruída.
+    KilledAccount { who: T::AccountId, dust: T::Balance },
+    /// `amount` do saldo livre de `who` foi reservado.
+    Reserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` do saldo reservado de `who` voltou para o saldo livre.
+    Unreserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Fundos foram retirados de uma conta e destruídos, como para pagar uma taxa.
+    Withdraw {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Fundos foram criados numa conta existente, como para devolver parte de uma taxa.
+    Deposit {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Fundos reservados foram destruídos.
+    Slashed {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// `amount` do saldo reservado de `from` foi movido para o saldo `status` de `to`.
+    ReserveRepatriated {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+        status: BalanceStatus,
+    },
+}
+
+/// O estado inicial deste pallet, usado para construir o bloco gênese.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config> {
+    /// O saldo livre de cada conta.
+    pub balances: Vec<(T::AccountId, T::Balance)>,
+    /// O saldo reservado de contas que também têm um saldo livre.
+    pub reserved: Vec<(T::AccountId, T::Balance)>,
+    /// Os bloqueios de contas que têm um saldo livre.
+    pub locks: Vec<(T::AccountId, BalanceLock<T::Balance>)>,
+}
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig {
+            balances: Vec::new(),
+            reserved: Vec::new(),
+            locks: Vec::new(),
+        }
+    }
+}
+
+/// Este é o Módulo de Saldos.
+/// Ele guarda o saldo de cada conta e permite transferir fundos entre elas.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pallet<T: Config> {
+    /// O saldo livre de cada conta. Contas com menos que o depósito existencial nunca são
+    /// guardadas.
     balances: BTreeMap<T::AccountId, T::Balance>,
+    /// Fundos guardados para algum propósito, como um depósito, que não podem ser usados até
+    /// voltarem ao saldo livre. Só contas com um saldo livre podem ter um saldo reservado.
+    reserved: BTreeMap<T::AccountId, T::Balance>,
+    /// Só contas com um saldo livre podem ter bloqueios.
+    locks: BTreeMap<T::AccountId, Vec<BalanceLock<T::Balance>>>,
+    /// Sempre igual à soma de todos os saldos livres e reservados.
+    total_issuance: T::Balance,
+    /// Os eventos emitidos desde a última coleta.
+    events: Vec<Event<T>>,
 }
 
 impl<T: Config> Pallet<T> {
+    /// Cria uma nova instância do Módulo de Saldos, sem nenhuma conta.
     pub fn new() -> Self {
         Pallet {
             balances: BTreeMap::new(),
+            reserved: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            total_issuance: T::Balance::zero(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Cria o pallet a partir do estado do bloco gênese.
+    ///
+    /// Entra em pânico se uma conta receber mais de um saldo livre, mais de um saldo reservado, ou
+    /// mais de um bloqueio com o mesmo nome, já que um deles substituiria os outros em silêncio.
+    pub fn from_genesis(config: GenesisConfig<T>) -> Self {
+        let mut pallet = Self::new();
+        let mut seen = BTreeSet::new();
+        for (who, amount) in config.balances {
+            assert!(
+                seen.insert(who.clone()),
+                "duplicate genesis balance: {:?}",
+                who
+            );
+            pallet
+                .set_balance(&who, amount)
+                .expect("invalid genesis balance");
+        }
+        // Os fundos reservados são criados além do saldo livre.
+        let mut seen = BTreeSet::new();
+        for (who, amount) in config.reserved {
+            assert!(
+                seen.insert(who.clone()),
+                "duplicate genesis reserved balance: {:?}",
+                who
+            );
+            pallet
+                .mint(&who, amount)
+                .and_then(|()| pallet.reserve(&who, amount))
+                .expect("invalid genesis reserved balance");
+        }
+        for (who, lock) in config.locks {
+            assert!(
+                !pallet.locks(&who).iter().any(|other| other.id == lock.id),
+                "duplicate genesis lock: {:?}",
+                who
+            );
+            pallet
+                .set_lock(lock.id, &who, lock.amount, lock.reasons)
+                .expect("invalid genesis lock");
+        }
+        // Os saldos do gênese fazem parte do estado inicial, não de algo que aconteceu na cadeia.
+        pallet.take_events();
+        pallet
+    }
+
+    /// Exporta os saldos atuais como uma `GenesisConfig`, para iniciar uma nova cadeia a partir
+    /// deste estado.
+    pub fn genesis_config(&self) -> GenesisConfig<T> {
+        GenesisConfig {
+            balances: self.balances.clone().into_iter().collect(),
+            reserved: self.reserved.clone().into_iter().collect(),
+            locks: self
+                .locks
+                .iter()
+                .flat_map(|(who, locks)| locks.iter().map(|lock| (who.clone(), lock.clone())))
+                .collect(),
+        }
+    }
+
+    /// Cria ou destrói a diferença entre o saldo atual de `who` e `amount`.
+    pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let balance = self.balance(who);
+        match amount.checked_sub(&balance) {
+            Some(increase) if increase.is_zero() => Ok(()),
+            Some(increase) => self.mint(who, increase),
+            None => {
+                let decrease = balance
+                    .checked_sub(&amount)
+                    .ok_or(ArithmeticError::Underflow)?;
+                self.burn(who, decrease)
+            }
+        }
+    }
+
+    /// Cria `amount` novos fundos na conta de `who`, criando a conta se necessário.
+    ///
+    /// Esta função é privilegiada: ela não é exposta como uma chamada, então só o runtime pode
+    /// usá-la.
+    pub fn mint(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let new_balance = self
+            .balance(who)
+            .checked_add(&amount)
+            .ok_or(ArithmeticError::Overflow)?;
+        let new_issuance = self
+            .total_issuance
+            .checked_add(&amount)
+            .ok_or(ArithmeticError::Overflow)?;
+        if new_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::BelowExistentialDeposit.into());
+        }
+
+        if self.balances.insert(who.clone(), new_balance).is_none() {
+            self.deposit_event(Event::NewAccount { who: who.clone() });
+        }
+        self.total_issuance = new_issuance;
+        self.deposit_event(Event::Minted {
+            who: who.clone(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Destrói `amount` fundos da conta de `who`.
+    ///
+    /// Esta função é privilegiada: ela não é exposta como uma chamada, então só o runtime pode
+    /// usá-la. Ela nunca remove a conta, já que só as chamadas alcançam o pallet de sistema para
+    /// limpar o que resta dela.
+    pub fn burn(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let new_balance = self
+            .balance(who)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        if new_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::BelowExistentialDeposit.into());
+        }
+        let new_issuance = self
+            .total_issuance
+            .checked_sub(&amount)
+            .ok_or(ArithmeticError::Underflow)?;
+
+        self.balances.insert(who.clone(), new_balance);
+        self.total_issuance = new_issuance;
+        self.deposit_event(Event::Burned {
+            who: who.clone(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Reserva `amount` do saldo livre de `who`, como para um depósito. Reservar nunca remove a
+    /// conta, e é limitado pelos bloqueios dela.
+    pub fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        let new_balance = self
+            .balance(who)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        if new_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::BelowExistentialDeposit.into());
+        }
+        self.ensure_can_withdraw(who, Reasons::Misc, new_balance)?;
+        let new_reserved = self
+            .reserved_balance(who)
+            .checked_add(&amount)
+            .expect("the total issuance includes every balance; qed");
+
+        self.balances.insert(who.clone(), new_balance);
+        self.reserved.insert(who.clone(), new_reserved);
+        self.deposit_event(Event::Reserved {
+            who: who.clone(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Devolve até `amount` do saldo reservado de `who` ao seu saldo livre. Retorna a parte de
+    /// `amount` que não estava reservada.
+    pub fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let (actual, remaining) = self.take_reserved(who, amount);
+        if actual.is_zero() {
+            return remaining;
+        }
+        let new_balance = self
+            .balance(who)
+            .checked_add(&actual)
+            .expect("the total issuance includes every balance; qed");
+        self.balances.insert(who.clone(), new_balance);
+        self.deposit_event(Event::Unreserved {
+            who: who.clone(),
+            amount: actual,
+        });
+        remaining
+    }
+
+    /// Destrói até `amount` do saldo reservado de `who`. Retorna a parte de `amount` que não
+    /// estava reservada.
+    pub fn slash_reserved(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let (actual, remaining) = self.take_reserved(who, amount);
+        if actual.is_zero() {
+            return remaining;
         }
+        self.total_issuance = self
+            .total_issuance
+            .checked_sub(&actual)
+            .expect("the total issuance includes every balance; qed");
+        self.deposit_event(Event::Slashed {
+            who: who.clone(),
+            amount: actual,
+        });
+        remaining
     }
 
-    pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
-        self.balances.insert(who.clone(), amount);
+    /// Move até `amount` do saldo reservado de `slashed` para o saldo livre ou reservado de
+    /// `beneficiary`, que precisa existir. Retorna a parte de `amount` que não estava reservada.
+    pub fn repatriate_reserved(
+        &mut self,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        amount: T::Balance,
+        status: BalanceStatus,
+    ) -> Result<T::Balance, DispatchError> {
+        if !self.balances.contains_key(beneficiary) {
+            return Err(Error::DeadAccount.into());
+        }
+        if slashed == beneficiary {
+            return Ok(match status {
+                BalanceStatus::Free => self.unreserve(slashed, amount),
+                BalanceStatus::Reserved => amount
+                    .checked_sub(&self.reserved_balance(slashed))
+                    .unwrap_or_else(T::Balance::zero),
+            });
+        }
+
+        let (actual, remaining) = self.take_reserved(slashed, amount);
+        // Nada foi movido, então o beneficiário não deve ganhar uma entrada vazia no armazenamento.
+        if actual.is_zero() {
+            return Ok(remaining);
+        }
+        let balances = match status {
+            BalanceStatus::Free => &mut self.balances,
+            BalanceStatus::Reserved => &mut self.reserved,
+        };
+        let balance = balances
+            .entry(beneficiary.clone())
+            .or_insert_with(T::Balance::zero);
+        *balance = balance
+            .checked_add(&actual)
+            .expect("the total issuance includes every balance; qed");
+        self.deposit_event(Event::ReserveRepatriated {
+            from: slashed.clone(),
+            to: beneficiary.clone(),
+            amount: actual,
+            status,
+        });
+
+        Ok(remaining)
+    }
+
+    /// Bloqueia `amount` do saldo livre de `who` para `reasons`, substituindo o bloqueio chamado
+    /// `id`, se houver.
+    pub fn set_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: Reasons,
+    ) -> DispatchResult {
+        if !self.balances.contains_key(who) {
+            return Err(Error::DeadAccount.into());
+        }
+        let locks = self.locks.entry(who.clone()).or_default();
+        let lock = BalanceLock {
+            id,
+            amount,
+            reasons,
+        };
+        match locks.iter_mut().find(|lock| lock.id == id) {
+            Some(existing) => *existing = lock,
+            None => locks.push(lock),
+        }
+        Ok(())
     }
 
+    /// Remove o bloqueio chamado `id` da conta de `who`, se houver.
+    pub fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+        if let Some(locks) = self.locks.get_mut(who) {
+            locks.retain(|lock| lock.id != id);
+            if locks.is_empty() {
+                self.locks.remove(who);
+            }
+        }
+    }
+
+    /// Obtém o saldo livre de `who`.
     pub fn balance(&self, who: &T::AccountId) -> T::Balance {
         *self.balances.get(who).unwrap_or(&T::Balance::zero())
     }
+
+    /// Obtém o saldo reservado de `who`.
+    pub fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+        *self.reserved.get(who).unwrap_or(&T::Balance::zero())
+    }
+
+    /// Obtém os bloqueios da conta de `who`.
+    pub fn locks(&self, who: &T::AccountId) -> &[BalanceLock<T::Balance>] {
+        self.locks.get(who).map_or(&[], Vec::as_slice)
+    }
+
+    /// Obtém a soma de todos os saldos livres e reservados.
+    pub fn total_issuance(&self) -> T::Balance {
+        self.total_issuance
+    }
+
+    /// Entra em pânico se a soma de todos os saldos não for o total emitido. Toda mudança nos
+    /// saldos deve mantê-los em sincronia, então isto só falha se houver um bug neste pallet.
+    pub fn assert_total_issuance(&self) {
+        let sum = self
+            .balances
+            .values()
+            .chain(self.reserved.values())
+            .try_fold(T::Balance::zero(), |sum, amount| sum.checked_add(amount))
+            .expect("the sum of all balances overflows");
+        assert_eq!(
+            sum, self.total_issuance,
+            "the sum of all balances is not the total issuance"
+        );
+    }
+
+    /// Falha se os bloqueios da conta de `who` para `reasons` não permitirem que o seu saldo
+    /// livre caia para `new_balance`.
+    fn ensure_can_withdraw(
+        &self,
+        who: &T::AccountId,
+        reasons: Reasons,
+        new_balance: T::Balance,
+    ) -> DispatchResult {
+        let locked = self
+            .locks(who)
+            .iter()
+            .any(|lock| lock.reasons.intersects(reasons) && new_balance < lock.amount);
+        if locked {
+            return Err(Error::LiquidityRestrictions.into());
+        }
+        Ok(())
+    }
+
+    /// Retira até `amount` do saldo reservado de `who`. Retorna o valor retirado, e a parte de
+    /// `amount` que não estava reservada.
+    fn take_reserved(
+        &mut self,
+        who: &T::AccountId,
+        amount: T::Balance,
+    ) -> (T::Balance, T::Balance) {
+        let reserved = self.reserved_balance(who);
+        match reserved.checked_sub(&amount) {
+            Some(left) => {
+                if left.is_zero() {
+                    self.reserved.remove(who);
+                } else {
+                    self.reserved.insert(who.clone(), left);
+                }
+                (amount, T::Balance::zero())
+            }
+            None => {
+                self.reserved.remove(who);
+                let remaining = amount
+                    .checked_sub(&reserved)
+                    .expect("`amount` is more than `reserved`; qed");
+                (reserved, remaining)
+            }
+        }
+    }
+
+    /// Move `amount` de `from` para `to`, criando a conta de `to` se necessário. Retorna o saldo
+    /// que sobrou na conta de `from`, que não é removida aqui mesmo se ficar abaixo do depósito
+    /// existencial.
+    ///
+    /// Com `ExistenceRequirement::AllowDeath`, `from` só pode ficar abaixo do depósito existencial
+    /// se não tiver saldo reservado nem bloqueios, que seriam perdidos quando a conta fosse
+    /// removida.
+    fn move_balance(
+        &mut self,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+        existence: ExistenceRequirement,
+    ) -> Result<T::Balance, DispatchError> {
+        let new_from_balance = self
+            .balance(from)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        self.ensure_can_withdraw(from, Reasons::Misc, new_from_balance)?;
+        let keep_alive = existence == ExistenceRequirement::KeepAlive
+            || self.reserved.contains_key(from)
+            || self.locks.contains_key(from);
+        if from != to && keep_alive && new_from_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::WouldReapAccount.into());
+        }
+        // A retirada vem primeiro, então uma transferência para si mesmo não muda o saldo.
+        let to_balance = if from == to {
+            new_from_balance
+        } else {
+            self.balance(to)
+        };
+        let new_to_balance = to_balance
+            .checked_add(&amount)
+            .ok_or(ArithmeticError::Overflow)?;
+        if new_to_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::BelowExistentialDeposit.into());
+        }
+
+        if !amount.is_zero() {
+            self.balances.insert(from.clone(), new_from_balance);
+            if self.balances.insert(to.clone(), new_to_balance).is_none() {
+                self.deposit_event(Event::NewAccount { who: to.clone() });
+            }
+        }
+        self.deposit_event(Event::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        });
+
+        Ok(self.balance(from))
+    }
+
+    /// Retira `amount` do saldo livre de `who` e o destrói, se os bloqueios da conta permitirem
+    /// para `reasons`. Retorna o saldo que sobrou, e a conta não é removida aqui mesmo se ficar
+    /// abaixo do depósito existencial.
+    ///
+    /// Como em `move_balance`, `who` só pode ficar abaixo do depósito existencial com
+    /// `ExistenceRequirement::AllowDeath`, e se não tiver saldo reservado nem bloqueios.
+    fn withdraw_balance(
+        &mut self,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: Reasons,
+        existence: ExistenceRequirement,
+    ) -> Result<T::Balance, DispatchError> {
+        let new_balance = self
+            .balance(who)
+            .checked_sub(&amount)
+            .ok_or(Error::InsufficientBalance)?;
+        self.ensure_can_withdraw(who, reasons, new_balance)?;
+        let keep_alive = existence == ExistenceRequirement::KeepAlive
+            || self.reserved.contains_key(who)
+            || self.locks.contains_key(who);
+        if keep_alive && new_balance < T::EXISTENTIAL_DEPOSIT {
+            return Err(Error::WouldReapAccount.into());
+        }
+        let new_issuance = self
+            .total_issuance
+            .checked_sub(&amount)
+            .ok_or(ArithmeticError::Underflow)?;
+
+        if !amount.is_zero() {
+            self.balances.insert(who.clone(), new_balance);
+            self.total_issuance = new_issuance;
+        }
+        self.deposit_event(Event::Withdraw {
+            who: who.clone(),
+            amount,
+        });
+
+        Ok(new_balance)
+    }
+
+    /// Cria `amount` no saldo livre de `who`, que já precisa ter uma conta.
+    fn deposit_into_existing(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let balance = *self.balances.get(who).ok_or(Error::DeadAccount)?;
+        let new_balance = balance
+            .checked_add(&amount)
+            .ok_or(ArithmeticError::Overflow)?;
+        let new_issuance = self
+            .total_issuance
+            .checked_add(&amount)
+            .ok_or(ArithmeticError::Overflow)?;
+
+        self.balances.insert(who.clone(), new_balance);
+        self.total_issuance = new_issuance;
+        self.deposit_event(Event::Deposit {
+            who: who.clone(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a conta de `who`, destruindo o que sobrou do saldo como poeira. Retorna se a conta
+    /// existia, caso em que o pallet de sistema também deve esquecê-la.
+    fn remove_account(&mut self, who: &T::AccountId) -> bool {
+        let Some(dust) = self.balances.remove(who) else {
+            return false;
+        };
+        self.total_issuance = self
+            .total_issuance
+            .checked_sub(&dust)
+            .expect("the total issuance includes every balance; qed");
+        self.deposit_event(Event::KilledAccount {
+            who: who.clone(),
+            dust,
+        });
+        true
+    }
+
+    /// Registra um evento para ser coletado pelo runtime ao final da chamada.
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// Remove e retorna todos os eventos emitidos desde a última coleta.
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// A chave do saldo livre de `who` no armazenamento deste pallet. Usada para provar o
+    /// resultado de `balance` contra uma raiz de estado.
+    pub fn balance_key(who: &T::AccountId) -> Vec<u8> {
+        storage_key("balances", who)
+    }
+
+    /// As entradas de armazenamento deste pallet, que o runtime usa para calcular a raiz de estado.
+    pub fn storage_entries(&self) -> Vec<StorageEntry> {
+        let mut entries = vec![(
+            storage_key("total_issuance", &()),
+            self.total_issuance.encode(),
+        )];
+        entries.extend(
+            self.balances
+                .iter()
+                .map(|(who, amount)| (Self::balance_key(who), amount.encode())),
+        );
+        entries.extend(
+            self.reserved
+                .iter()
+                .map(|(who, amount)| (storage_key("reserved", who), amount.encode())),
+        );
+        entries.extend(
+            self.locks
+                .iter()
+                .map(|(who, locks)| (storage_key("locks", who), locks.encode())),
+        );
+        entries
+    }
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
+    /// Transfere `amount` para `to`. Se o chamador ficar com menos que o depósito existencial, a
+    /// sua conta é removida, a não ser que tenha fundos reservados ou bloqueios. Fundos bloqueados
+    /// não podem ser transferidos.
+    ///
+    /// O peso supõe que a conta é removida, e o peso extra é devolvido quando ela não é.
+    #[weight(15_000)]
     pub fn transfer(
         &mut self,
+        runtime: &mut T,
         caller: T::AccountId,
         to: T::AccountId,
         amount: T::Balance,
-    ) -> crate::support::DispatchResult {
-        let caller_balance = self.balance(&caller);
-        let to_balance = self.balance(&to);
+    ) -> DispatchResultWithPostInfo {
+        let remaining =
+            self.move_balance(&caller, &to, amount, ExistenceRequirement::AllowDeath)?;
+        if remaining < T::EXISTENTIAL_DEPOSIT && self.remove_account(&caller) {
+            kill_account(runtime, &caller);
+            return Ok(PostDispatchInfo::default());
+        }
+        Ok(PostDispatchInfo {
+            actual_weight: Some(10_000),
+        })
+    }
 
-        let new_caller_balance = caller_balance
-            .checked_sub(&amount)
-            .ok_or("Insufficient balance")?;
-        let new_to_balance = to_balance.checked_add(&amount).ok_or("Overflow")?;
+    /// Como `transfer`, mas falha em vez de remover a conta do chamador.
+    #[weight(10_000)]
+    pub fn transfer_keep_alive(
+        &mut self,
+        caller: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        self.move_balance(&caller, &to, amount, ExistenceRequirement::KeepAlive)?;
+        Ok(())
+    }
+}
+
+/// O pallet de sistema esquece uma conta removida, o que zera o seu nonce.
+fn kill_account<T: Config>(runtime: &mut T, who: &T::AccountId) {
+    let system: &mut crate::system::Pallet<T> = runtime.pallet_mut();
+    system.kill_account(who);
+}
+
+/// Os erros deste pallet são retornados ao pallet que usou a moeda, então recebem o índice deste
+/// pallet antes que o runtime lhes dê o índice do pallet chamador.
+fn with_index<T: Config + HasPallet<Pallet<T>>, R>(
+    result: Result<R, DispatchError>,
+) -> Result<R, DispatchError> {
+    result.map_err(|error| error.with_pallet_index(<T as HasPallet<Pallet<T>>>::INDEX))
+}
 
-        self.balances.insert(caller, new_caller_balance);
-        self.balances.insert(to, new_to_balance);
+/// O pallet de saldos não faz nada ao final de um bloco.
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
 
+/// Outros pallets usam os saldos através do runtime, de onde este pallet não é retirado enquanto
+/// eles executam, então estas funções o obtêm do runtime.
+impl<T: Config + HasPallet<Pallet<T>>> Currency<T> for Pallet<T> {
+    type AccountId = T::AccountId;
+    type Balance = T::Balance;
+
+    fn minimum_balance() -> T::Balance {
+        T::EXISTENTIAL_DEPOSIT
+    }
+
+    fn total_issuance(runtime: &T) -> T::Balance {
+        let balances: &Pallet<T> = runtime.pallet();
+        balances.total_issuance()
+    }
+
+    fn free_balance(runtime: &T, who: &T::AccountId) -> T::Balance {
+        let balances: &Pallet<T> = runtime.pallet();
+        balances.balance(who)
+    }
+
+    fn transfer(
+        runtime: &mut T,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        let remaining = with_index::<T, _>(balances.move_balance(from, to, amount, existence))?;
+        if remaining < T::EXISTENTIAL_DEPOSIT && balances.remove_account(from) {
+            kill_account(runtime, from);
+        }
         Ok(())
     }
+
+    fn withdraw(
+        runtime: &mut T,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reason: WithdrawReason,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        let remaining =
+            with_index::<T, _>(balances.withdraw_balance(who, amount, reason.into(), existence))?;
+        if remaining < T::EXISTENTIAL_DEPOSIT && balances.remove_account(who) {
+            kill_account(runtime, who);
+        }
+        Ok(())
+    }
+
+    fn deposit_into_existing(
+        runtime: &mut T,
+        who: &T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        with_index::<T, _>(balances.deposit_into_existing(who, amount))
+    }
 }
 
-#[cfg(test)]
+impl<T: Config + HasPallet<Pallet<T>>> ReservableCurrency<T> for Pallet<T> {
+    fn reserved_balance(runtime: &T, who: &T::AccountId) -> T::Balance {
+        let balances: &Pallet<T> = runtime.pallet();
+        balances.reserved_balance(who)
+    }
 
-mod tests {
-    use std::u128;
+    fn reserve(runtime: &mut T, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        with_index::<T, _>(balances.reserve(who, amount))
+    }
 
-    struct TestConfig;
+    fn unreserve(runtime: &mut T, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        balances.unreserve(who, amount)
+    }
 
-    impl super::Config for TestConfig {
-        type Balance = u128;
+    fn slash_reserved(runtime: &mut T, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        balances.slash_reserved(who, amount)
     }
 
-    impl crate::system::Config for TestConfig {
-        type AccountId = String;
-        type BlockNumber = u32;
-        type Nonce = u32;
+    fn repatriate_reserved(
+        runtime: &mut T,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        amount: T::Balance,
+        status: BalanceStatus,
+    ) -> Result<T::Balance, DispatchError> {
+        let balances: &mut Pallet<T> = runtime.pallet_mut();
+        with_index::<T, _>(balances.repatriate_reserved(slashed, beneficiary, amount, status))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Event, Reasons};
+    use crate::mock::TestRuntime;
+    use crate::support::{
+        ArithmeticError, BalanceStatus, DispatchError, ExistenceRequirement, PostDispatchInfo,
+        ReservableCurrency,
+    };
 
     #[test]
     fn init_balances() {
-        let mut balances = super::Pallet::<TestConfig>::new();
+        let mut balances = super::Pallet::<TestRuntime>::new();
 
-        assert_eq!(balances.balance(&"alice".to_string()), 0);
+        assert_eq!(balances.balance(&"alice"), 0);
 
-        balances.set_balance(&"alice".to_string(), 100);
-        assert_eq!(balances.balance(&"alice".to_string()), 100);
+        balances.set_balance(&"alice", 100).unwrap();
+        assert_eq!(balances.balance(&"alice"), 100);
 
-        assert_eq!(balances.balance(&"bob".to_string()), 0);
+        assert_eq!(balances.balance(&"bob"), 0);
     }
 
     #[test]
 
     fn transfer_balance() {
-        let mut balances = super::Pallet::<TestConfig>::new();
+        let mut runtime = TestRuntime::new();
+        let mut balances = super::Pallet::<TestRuntime>::new();
 
         assert_eq!(
-            balances.transfer("daniel".to_string(), "vini".to_string(), 10),
-            Err("Insufficient balance")
+            balances.transfer(&mut runtime, "daniel", "vini", 10),
+            Err(Error::InsufficientBalance.into())
         );
 
-        balances.set_balance(&"daniel".to_string(), 10);
+        balances.set_balance(&"daniel", 10).unwrap();
+        balances.take_events();
         assert_eq!(
-            balances.transfer("daniel".to_string(), "vini".to_string(), 3),
-            Ok(())
+            balances.transfer(&mut runtime, "daniel", "vini", 3),
+            Ok(PostDispatchInfo {
+                actual_weight: Some(10_000)
+            })
         );
-        assert_eq!(balances.balance(&"daniel".to_string()), 7);
-        assert_eq!(balances.balance(&"vini".to_string()), 3);
+        assert_eq!(balances.balance(&"daniel"), 7);
+        assert_eq!(balances.balance(&"vini"), 3);
+        assert_eq!(
+            balances.take_events(),
+            vec![
+                Event::NewAccount { who: "vini" },
+                Event::Transfer {
+                    from: "daniel",
+                    to: "vini",
+                    amount: 3,
+                },
+            ]
+        );
+        assert!(balances.take_events().is_empty());
+
+        // O total emitido torna este overflow impossível, então o contornamos para testar
+        // `transfer`.
+        balances.balances.insert("vini", u128::MAX);
+        assert_eq!(
+            balances.transfer(&mut runtime, "daniel", "vini", 3),
+            Err(ArithmeticError::Overflow.into())
+        );
+    }
 
-        balances.set_balance(&"vini".to_string(), u128::MAX);
+    #[test]
+    fn total_issuance() {
+        let alice = "alice";
+        let bob = "bob";
+        let mut runtime = TestRuntime::new();
+        let mut balances = super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 100), (bob, 50)],
+            ..Default::default()
+        });
+        assert_eq!(balances.total_issuance(), 150);
+        // Os saldos do gênese não emitem eventos.
+        assert!(balances.take_events().is_empty());
+
+        balances.mint(&alice, 10).unwrap();
+        balances.burn(&bob, 20).unwrap();
+        balances.transfer(&mut runtime, alice, bob, 5).unwrap();
+        assert_eq!(balances.total_issuance(), 140);
+        balances.assert_total_issuance();
+        assert_eq!(
+            balances.take_events(),
+            vec![
+                Event::Minted {
+                    who: alice,
+                    amount: 10,
+                },
+                Event::Burned {
+                    who: bob,
+                    amount: 20,
+                },
+                Event::Transfer {
+                    from: alice,
+                    to: bob,
+                    amount: 5,
+                },
+            ]
+        );
+
+        // Definir um saldo cria ou destrói a diferença.
+        balances.set_balance(&alice, 2).unwrap();
+        balances.set_balance(&bob, 200).unwrap();
+        assert_eq!(balances.total_issuance(), 202);
+        balances.assert_total_issuance();
+
+        assert_eq!(
+            balances.burn(&alice, 3),
+            Err(Error::InsufficientBalance.into())
+        );
+        assert_eq!(
+            balances.mint(&alice, u128::MAX),
+            Err(ArithmeticError::Overflow.into())
+        );
+        assert_eq!(balances.total_issuance(), 202);
+    }
+
+    #[test]
+    fn existential_deposit() {
+        let alice = "alice";
+        let bob = "bob";
+        let charlie = "charlie";
+        let mut runtime = TestRuntime::new();
+        let mut balances = super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 10)],
+            ..Default::default()
+        });
+        runtime.system.inc_nonce(&alice);
+
+        // Contas não podem ser criadas, nem deixadas por funções privilegiadas, abaixo do depósito.
+        assert_eq!(
+            balances.mint(&bob, 1),
+            Err(Error::BelowExistentialDeposit.into())
+        );
+        assert_eq!(
+            balances.burn(&alice, 9),
+            Err(Error::BelowExistentialDeposit.into())
+        );
+        assert_eq!(
+            balances.transfer(&mut runtime, alice, bob, 1),
+            Err(Error::BelowExistentialDeposit.into())
+        );
+
+        balances.transfer(&mut runtime, alice, bob, 5).unwrap();
+        // Deixar poeira para trás falha com `transfer_keep_alive`, e remove a conta com `transfer`.
+        // A poeira é destruída, e o pallet de sistema esquece a conta.
+        assert_eq!(
+            balances.transfer_keep_alive(alice, charlie, 4),
+            Err(Error::WouldReapAccount.into())
+        );
+        // Remover a conta usa o peso inteiro da chamada.
+        assert_eq!(
+            balances.transfer(&mut runtime, alice, charlie, 4),
+            Ok(PostDispatchInfo::default())
+        );
+        assert_eq!(balances.balance(&alice), 0);
+        assert!(!balances.balances.contains_key(&alice));
+        assert_eq!(runtime.system.account_nonce(&alice), 0);
+        assert_eq!(balances.total_issuance(), 9);
+        balances.assert_total_issuance();
+        assert_eq!(
+            balances.take_events(),
+            vec![
+                Event::NewAccount { who: bob },
+                Event::Transfer {
+                    from: alice,
+                    to: bob,
+                    amount: 5,
+                },
+                Event::NewAccount { who: charlie },
+                Event::Transfer {
+                    from: alice,
+                    to: charlie,
+                    amount: 4,
+                },
+                Event::KilledAccount {
+                    who: alice,
+                    dust: 1,
+                },
+            ]
+        );
+
+        // Transferências que deixam pelo menos o depósito para trás mantêm a conta viva.
+        balances.transfer_keep_alive(bob, charlie, 3).unwrap();
+        assert_eq!(balances.balance(&bob), 2);
+        assert_eq!(balances.balance(&charlie), 7);
+        // Uma transferência de tudo remove a conta sem nenhuma poeira.
+        balances.transfer(&mut runtime, bob, charlie, 2).unwrap();
+        assert!(!balances.balances.contains_key(&bob));
+        assert_eq!(balances.total_issuance(), 9);
+        balances.assert_total_issuance();
+    }
+
+    #[test]
+    fn reserved_balances() {
+        let alice = "alice";
+        let bob = "bob";
+        let mut runtime = TestRuntime::new();
+        let mut balances = super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 100), (bob, 50)],
+            ..Default::default()
+        });
+
+        balances.reserve(&alice, 30).unwrap();
+        assert_eq!(balances.balance(&alice), 70);
+        assert_eq!(balances.reserved_balance(&alice), 30);
+        // Reservar não pode remover a conta.
+        assert_eq!(
+            balances.reserve(&alice, 69),
+            Err(Error::BelowExistentialDeposit.into())
+        );
+        assert_eq!(
+            balances.reserve(&alice, 71),
+            Err(Error::InsufficientBalance.into())
+        );
+
+        assert_eq!(balances.unreserve(&alice, 10), 0);
+        assert_eq!(balances.slash_reserved(&alice, 5), 0);
+        assert_eq!(
+            balances.repatriate_reserved(&alice, &bob, 10, BalanceStatus::Free),
+            Ok(0)
+        );
+        // Só os 5 que sobraram na reserva de alice podem ser movidos.
+        assert_eq!(
+            balances.repatriate_reserved(&alice, &bob, 10, BalanceStatus::Reserved),
+            Ok(5)
+        );
+        assert_eq!(
+            balances.repatriate_reserved(&bob, &"charlie", 5, BalanceStatus::Free),
+            Err(Error::DeadAccount.into())
+        );
+        assert_eq!(balances.unreserve(&alice, 10), 10);
+
+        assert_eq!(balances.balance(&alice), 80);
+        assert_eq!(balances.reserved_balance(&alice), 0);
+        assert_eq!(balances.balance(&bob), 60);
+        assert_eq!(balances.reserved_balance(&bob), 5);
+        // O corte destrói os fundos reservados.
+        assert_eq!(balances.total_issuance(), 145);
+        balances.assert_total_issuance();
+        assert_eq!(
+            balances.take_events(),
+            vec![
+                Event::Reserved {
+                    who: alice,
+                    amount: 30,
+                },
+                Event::Unreserved {
+                    who: alice,
+                    amount: 10,
+                },
+                Event::Slashed {
+                    who: alice,
+                    amount: 5,
+                },
+                Event::ReserveRepatriated {
+                    from: alice,
+                    to: bob,
+                    amount: 10,
+                    status: BalanceStatus::Free,
+                },
+                Event::ReserveRepatriated {
+                    from: alice,
+                    to: bob,
+                    amount: 5,
+                    status: BalanceStatus::Reserved,
+                },
+            ]
+        );
+
+        // Uma conta com fundos reservados nunca é removida, já que eles seriam perdidos.
+        assert_eq!(
+            balances.transfer(&mut runtime, bob, alice, 59),
+            Err(Error::WouldReapAccount.into())
+        );
+        balances.transfer(&mut runtime, bob, alice, 58).unwrap();
+        assert_eq!(balances.balance(&bob), 2);
+    }
+
+    #[test]
+    fn repatriate_nothing() {
+        let alice = "alice";
+        let bob = "bob";
+        let mut balances = super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 100), (bob, 50)],
+            ..Default::default()
+        });
+        let entries = balances.storage_entries();
+
+        // Mover nada, ou de uma reserva vazia, não altera o armazenamento.
+        assert_eq!(
+            balances.repatriate_reserved(&alice, &bob, 0, BalanceStatus::Reserved),
+            Ok(0)
+        );
+        assert_eq!(
+            balances.repatriate_reserved(&alice, &bob, 10, BalanceStatus::Reserved),
+            Ok(10)
+        );
+        assert!(!balances.reserved.contains_key(&bob));
+        assert_eq!(balances.storage_entries(), entries);
+        assert!(balances.take_events().is_empty());
+    }
+
+    #[test]
+    fn locks() {
+        let alice = "alice";
+        let bob = "bob";
+        let mut runtime = TestRuntime::new();
+        let mut balances = super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 100)],
+            ..Default::default()
+        });
+
+        balances
+            .set_lock(*b"staking ", &alice, 60, Reasons::Misc)
+            .unwrap();
+        assert_eq!(
+            balances.transfer(&mut runtime, alice, bob, 41),
+            Err(Error::LiquidityRestrictions.into())
+        );
+        assert_eq!(
+            balances.reserve(&alice, 41),
+            Err(Error::LiquidityRestrictions.into())
+        );
+        balances.transfer(&mut runtime, alice, bob, 40).unwrap();
+
+        // Definir um bloqueio com o mesmo nome o substitui.
+        balances
+            .set_lock(*b"staking ", &alice, 20, Reasons::All)
+            .unwrap();
+        // Bloqueios para taxas não limitam transferências.
+        balances
+            .set_lock(*b"fees    ", &alice, 60, Reasons::Fee)
+            .unwrap();
+        assert_eq!(balances.locks(&alice).len(), 2);
+        balances.transfer(&mut runtime, alice, bob, 40).unwrap();
+        assert_eq!(balances.balance(&alice), 20);
+
+        // Uma conta com bloqueios nunca é removida.
+        balances.remove_lock(*b"staking ", &alice);
+        assert_eq!(
+            balances.transfer(&mut runtime, alice, bob, 20),
+            Err(Error::WouldReapAccount.into())
+        );
+        balances.remove_lock(*b"fees    ", &alice);
+        assert!(balances.locks(&alice).is_empty());
+        balances.transfer(&mut runtime, alice, bob, 20).unwrap();
+        assert_eq!(balances.balance(&alice), 0);
+
+        assert_eq!(
+            balances.set_lock(*b"staking ", &alice, 10, Reasons::All),
+            Err(Error::DeadAccount.into())
+        );
+    }
+
+    #[test]
+    fn currency() {
+        currency_through_runtime::<super::Pallet<TestRuntime>>();
+    }
+
+    // Outros pallets só conhecem os saldos através das traits de `Currency`, como `Balances` aqui.
+    fn currency_through_runtime<Balances>()
+    where
+        Balances: ReservableCurrency<TestRuntime, AccountId = &'static str, Balance = u128>,
+    {
+        let alice = "alice";
+        let bob = "bob";
+        let mut runtime = TestRuntime::new();
+        runtime.balances = super::Pallet::from_genesis(super::GenesisConfig {
+            balances: vec![(alice, 100), (bob, 50)],
+            ..Default::default()
+        });
+        runtime.system.inc_nonce(&alice);
+
+        assert_eq!(Balances::minimum_balance(), 2);
+        assert_eq!(Balances::total_issuance(&runtime), 150);
+        assert_eq!(Balances::free_balance(&runtime, &alice), 100);
+
+        // As reservas movem fundos entre os saldos livre e reservado do pallet do runtime.
+        Balances::reserve(&mut runtime, &alice, 40).unwrap();
+        assert_eq!(Balances::free_balance(&runtime, &alice), 60);
+        assert_eq!(Balances::reserved_balance(&runtime, &alice), 40);
+        assert_eq!(Balances::unreserve(&mut runtime, &alice, 50), 10);
+        Balances::reserve(&mut runtime, &alice, 30).unwrap();
+        assert_eq!(
+            Balances::repatriate_reserved(&mut runtime, &alice, &bob, 20, BalanceStatus::Free),
+            Ok(0)
+        );
+        assert_eq!(Balances::slash_reserved(&mut runtime, &alice, 20), 10);
+        assert_eq!(Balances::free_balance(&runtime, &alice), 70);
+        assert_eq!(Balances::free_balance(&runtime, &bob), 70);
+        assert_eq!(Balances::total_issuance(&runtime), 140);
+
+        // Transferências que deixariam poeira para trás só removem a conta quando isso é permitido,
+        // e então o pallet de sistema também a esquece. Os erros mantêm o índice dos saldos no
+        // runtime.
+        let error = DispatchError::from(Error::WouldReapAccount);
+        assert_eq!(
+            Balances::transfer(
+                &mut runtime,
+                &alice,
+                &bob,
+                69,
+                ExistenceRequirement::KeepAlive
+            ),
+            Err(error.with_pallet_index(1))
+        );
+        Balances::transfer(
+            &mut runtime,
+            &alice,
+            &bob,
+            69,
+            ExistenceRequirement::AllowDeath,
+        )
+        .unwrap();
+        assert_eq!(Balances::free_balance(&runtime, &alice), 0);
+        assert_eq!(Balances::free_balance(&runtime, &bob), 139);
+        assert_eq!(runtime.system.account_nonce(&alice), 0);
+        assert_eq!(Balances::total_issuance(&runtime), 139);
+        runtime.balances.assert_total_issuance();
+        assert_eq!(
+            runtime.balances.take_events().last(),
+            Some(&Event::KilledAccount {
+                who: alice,
+                dust: 1
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the sum of all balances is not the total issuance")]
+    fn broken_total_issuance() {
+        let mut balances = super::Pallet::<TestRuntime>::new();
+        balances.balances.insert("alice", 1);
+        balances.assert_total_issuance();
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate genesis balance: \"alice\"")]
+    fn duplicate_genesis_balance() {
+        super::Pallet::<TestRuntime>::from_genesis(super::GenesisConfig {
+            balances: vec![("alice", 100), ("alice", 50)],
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn error_message() {
         assert_eq!(
-            balances.transfer("daniel".to_string(), "vini".to_string(), 3),
-            Err("Overflow")
+            Error::InsufficientBalance.to_string(),
+            "Insufficient balance"
         );
     }
 }
@@ -0,0 +1,711 @@
+use crate::{balances, crypto, proof_of_existence, system, types, Runtime, RuntimeGenesisConfig};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Uma especificação de cadeia: o nome da cadeia e o estado do seu bloco gênese.
+///
+/// Especificações são guardadas em arquivos JSON, onde as contas e os hashes reivindicados estão em
+/// hexadecimal e os saldos são números (ou strings, para saldos maiores que `u64`):
+///
+/// ```text
+/// {
+///   "name": "Development",
+///   "genesis": {
+///     "system": { "block_number": 0 },
+///     "balances": {
+///       "balances": [["0xd5bf…", 100]],
+///       "reserved": [["0xd5bf…", 10]],
+///       "locks": [["0xd5bf…", [118, 101, 115, 116, 105, 110, 103, 32], 50, "All"]]
+///     },
+///     "proof_of_existence": {
+///       "claims": [["0xe1bd…", "0xd5bf…", 5, 0, [82, 101, 100], null]],
+///       "pending_transfers": [["0xe1bd…", "0x8eaf…"]]
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChainSpec {
+    pub name: String,
+    pub genesis: RuntimeGenesisConfig,
+}
+
+/// O erro retornado quando um arquivo não é uma especificação de cadeia válida.
+#[derive(Debug)]
+pub enum Error {
+    /// O arquivo não é um JSON válido.
+    Json(serde_json::Error),
+    /// Um campo obrigatório não foi encontrado.
+    Missing(&'static str),
+    /// Um campo tem um valor inválido.
+    Invalid(&'static str),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "invalid json: {}", error),
+            Error::Missing(field) => write!(f, "missing field `{}`", field),
+            Error::Invalid(field) => write!(f, "invalid value for `{}`", field),
+        }
+    }
+}
+
+impl ChainSpec {
+    /// A cadeia de desenvolvimento, onde a conta `alice` começa com algum saldo.
+    pub fn development() -> Self {
+        let alice = crypto::Pair::from_name("alice").public();
+        ChainSpec {
+            name: "Development".to_string(),
+            genesis: RuntimeGenesisConfig {
+                balances: balances::GenesisConfig {
+                    balances: vec![(alice, 100)],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Exporta o estado atual de `runtime` como uma nova especificação, para iniciar um fork da
+    /// cadeia a partir deste estado.
+    pub fn from_runtime(name: &str, runtime: &Runtime) -> Self {
+        ChainSpec {
+            name: name.to_string(),
+            genesis: runtime.genesis_config(),
+        }
+    }
+
+    /// Cria o runtime com o estado gênese desta especificação.
+    pub fn build(self) -> Runtime {
+        Runtime::from_genesis(self.genesis)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let spec: Value = serde_json::from_str(json).map_err(Error::Json)?;
+        let name = field(&spec, "name")?
+            .as_str()
+            .ok_or(Error::Invalid("name"))?
+            .to_string();
+
+        let genesis = field(&spec, "genesis")?;
+        let system = field(genesis, "system")?;
+        let block_number = field(system, "block_number")?
+            .as_u64()
+            .and_then(|number| number.try_into().ok())
+            .ok_or(Error::Invalid("block_number"))?;
+        let balances_genesis = field(genesis, "balances")?;
+        let balances = field(balances_genesis, "balances")?;
+        let balances = pairs(balances, "balances", account_from_json, balance_from_json)?;
+        // Os saldos reservados e os bloqueios vieram depois, então especificações mais antigas não
+        // os têm.
+        let reserved = match balances_genesis.get("reserved") {
+            Some(reserved) => pairs(reserved, "reserved", account_from_json, balance_from_json)?,
+            None => Vec::new(),
+        };
+        let locks = match balances_genesis.get("locks") {
+            Some(locks) => locks_from_json(locks)?,
+            None => Vec::new(),
+        };
+        // Cada conta tem um único saldo livre e um único saldo reservado.
+        let unique = |pairs: &[(types::AccountId, types::Balance)]| {
+            pairs
+                .iter()
+                .map(|(who, _)| who)
+                .collect::<BTreeSet<_>>()
+                .len()
+                == pairs.len()
+        };
+        if !unique(&balances) {
+            return Err(Error::Invalid("balances"));
+        }
+        if !unique(&reserved) {
+            return Err(Error::Invalid("reserved"));
+        }
+        // O total emitido deve caber num saldo.
+        balances
+            .iter()
+            .chain(&reserved)
+            .try_fold(0 as types::Balance, |sum, (_, amount)| {
+                sum.checked_add(*amount)
+            })
+            .ok_or(Error::Invalid("balances"))?;
+        // Contas com menos que o depósito existencial não podem existir.
+        let existential_deposit = <Runtime as balances::Config>::EXISTENTIAL_DEPOSIT;
+        if balances
+            .iter()
+            .any(|(_, amount)| *amount < existential_deposit)
+        {
+            return Err(Error::Invalid("balances"));
+        }
+        // Só contas com um saldo livre podem ter fundos reservados ou bloqueios.
+        let exists = |who| balances.iter().any(|(account, _)| account == who);
+        if !reserved.iter().all(|(who, _)| exists(who)) {
+            return Err(Error::Invalid("reserved"));
+        }
+        if !locks.iter().all(|(who, _)| exists(who)) {
+            return Err(Error::Invalid("locks"));
+        }
+        let poe_genesis = field(genesis, "proof_of_existence")?;
+        let claims = claims_from_json(field(poe_genesis, "claims")?, block_number)?;
+        // As transferências pendentes vieram depois, então especificações mais antigas não as têm.
+        let pending_transfers = match poe_genesis.get("pending_transfers") {
+            Some(transfers) => pairs(
+                transfers,
+                "pending_transfers",
+                content_from_json,
+                account_from_json,
+            )?,
+            None => Vec::new(),
+        };
+        // Só reivindicações existentes podem ser transferidas.
+        if !pending_transfers
+            .iter()
+            .all(|(claim, _)| claims.iter().any(|(other, _)| other == claim))
+        {
+            return Err(Error::Invalid("pending_transfers"));
+        }
+        // Os depósitos das reivindicações de cada conta já devem estar reservados.
+        let mut deposits = BTreeMap::new();
+        for (_, info) in &claims {
+            let total: &mut types::Balance = deposits.entry(&info.owner).or_default();
+            *total = total
+                .checked_add(info.deposit)
+                .ok_or(Error::Invalid("claims"))?;
+        }
+        for (owner, total) in deposits {
+            let reserved = reserved
+                .iter()
+                .find(|(who, _)| who == owner)
+                .map_or(0, |(_, amount)| *amount);
+            if total > reserved {
+                return Err(Error::Invalid("claims"));
+            }
+        }
+
+        Ok(ChainSpec {
+            name,
+            genesis: RuntimeGenesisConfig {
+                system: system::GenesisConfig { block_number },
+                balances: balances::GenesisConfig {
+                    balances,
+                    reserved,
+                    locks,
+                },
+                proof_of_existence: proof_of_existence::GenesisConfig {
+                    claims,
+                    pending_transfers,
+                },
+                transaction_payment: Default::default(),
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let genesis = &self.genesis;
+        let balances = |balances: &[(types::AccountId, types::Balance)]| {
+            balances
+                .iter()
+                .map(|(who, amount)| json!([who.to_hex(), balance_to_json(*amount)]))
+                .collect::<Vec<_>>()
+        };
+        let locks = genesis
+            .balances
+            .locks
+            .iter()
+            .map(|(who, lock)| {
+                json!([
+                    who.to_hex(),
+                    lock.id,
+                    balance_to_json(lock.amount),
+                    format!("{:?}", lock.reasons),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let pending_transfers = genesis
+            .proof_of_existence
+            .pending_transfers
+            .iter()
+            .map(|(claim, new_owner)| json!([claim.to_hex(), new_owner.to_hex()]))
+            .collect::<Vec<_>>();
+        let claims = genesis
+            .proof_of_existence
+            .claims
+            .iter()
+            .map(|(claim, info)| {
+                json!([
+                    claim.to_hex(),
+                    info.owner.to_hex(),
+                    balance_to_json(info.deposit),
+                    info.block_number,
+                    info.description,
+                    info.expires_at,
+                ])
+            })
+            .collect::<Vec<_>>();
+        let spec = json!({
+            "name": self.name,
+            "genesis": {
+                "system": { "block_number": genesis.system.block_number },
+                "balances": {
+                    "balances": balances(&genesis.balances.balances),
+                    "reserved": balances(&genesis.balances.reserved),
+                    "locks": locks,
+                },
+                "proof_of_existence": {
+                    "claims": claims,
+                    "pending_transfers": pending_transfers,
+                },
+            },
+        });
+        serde_json::to_string_pretty(&spec).expect("a json value can always be serialized; qed")
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &'static str) -> Result<&'a Value, Error> {
+    value.get(name).ok_or(Error::Missing(name))
+}
+
+/// Lê uma lista de pares `[a, b]`. Cada `a` deve aparecer no máximo uma vez, já que é a chave de
+/// um mapa no armazenamento do pallet.
+fn pairs<A: Ord + Clone, B>(
+    value: &Value,
+    name: &'static str,
+    first: impl Fn(&Value) -> Option<A>,
+    second: impl Fn(&Value) -> Option<B>,
+) -> Result<Vec<(A, B)>, Error> {
+    let items = value.as_array().ok_or(Error::Invalid(name))?;
+    let mut keys = BTreeSet::new();
+    let mut pairs = Vec::new();
+    for item in items {
+        let (a, b) = match item.as_array().map(Vec::as_slice) {
+            Some([a, b]) => (first(a), second(b)),
+            _ => (None, None),
+        };
+        let (Some(a), Some(b)) = (a, b) else {
+            return Err(Error::Invalid(name));
+        };
+        if !keys.insert(a.clone()) {
+            return Err(Error::Invalid(name));
+        }
+        pairs.push((a, b));
+    }
+    Ok(pairs)
+}
+
+fn account_from_json(value: &Value) -> Option<types::AccountId> {
+    crypto::Public::from_hex(value.as_str()?)
+}
+
+fn locks_from_json(
+    value: &Value,
+) -> Result<Vec<(types::AccountId, balances::BalanceLock<types::Balance>)>, Error> {
+    let items = value.as_array().ok_or(Error::Invalid("locks"))?;
+    let mut locks: Vec<(types::AccountId, balances::BalanceLock<types::Balance>)> = Vec::new();
+    for item in items {
+        let lock = match item.as_array().map(Vec::as_slice) {
+            Some([who, id, amount, reasons]) => lock_from_json(who, id, amount, reasons),
+            _ => None,
+        };
+        let (who, lock) = lock.ok_or(Error::Invalid("locks"))?;
+        // Cada conta só pode ter um bloqueio com cada nome.
+        if locks
+            .iter()
+            .any(|(other, other_lock)| other == &who && other_lock.id == lock.id)
+        {
+            return Err(Error::Invalid("locks"));
+        }
+        locks.push((who, lock));
+    }
+    Ok(locks)
+}
+
+fn lock_from_json(
+    who: &Value,
+    id: &Value,
+    amount: &Value,
+    reasons: &Value,
+) -> Option<(types::AccountId, balances::BalanceLock<types::Balance>)> {
+    let id = bytes_from_json(id)?;
+    let reasons = match reasons.as_str()? {
+        "Fee" => balances::Reasons::Fee,
+        "Misc" => balances::Reasons::Misc,
+        "All" => balances::Reasons::All,
+        _ => return None,
+    };
+    let lock = balances::BalanceLock {
+        id: id.try_into().ok()?,
+        amount: balance_from_json(amount)?,
+        reasons,
+    };
+    Some((account_from_json(who)?, lock))
+}
+
+/// Lê a lista de reivindicações `[conteúdo, proprietário, depósito, bloco, descrição, expiração]`,
+/// onde a descrição é uma lista de bytes ou `null`, e a expiração é um bloco ou `null`.
+/// Especificações mais antigas não têm os últimos campos: essas reivindicações têm depósito zero,
+/// são do bloco zero, não têm descrição e não expiram.
+fn claims_from_json(
+    value: &Value,
+    block_number: types::BlockNumber,
+) -> Result<Vec<(types::Content, types::ClaimInfo)>, Error> {
+    let items = value.as_array().ok_or(Error::Invalid("claims"))?;
+    let mut contents = BTreeSet::new();
+    let mut claims_per_owner = BTreeMap::<types::AccountId, usize>::new();
+    let mut claims = Vec::new();
+    for item in items {
+        let claim = match item.as_array().map(Vec::as_slice) {
+            Some([claim, owner]) => claim_from_json(
+                claim,
+                owner,
+                &json!(0),
+                &json!(0),
+                &Value::Null,
+                &Value::Null,
+            ),
+            Some([claim, owner, deposit]) => {
+                claim_from_json(claim, owner, deposit, &json!(0), &Value::Null, &Value::Null)
+            }
+            Some([claim, owner, deposit, block, description]) => {
+                claim_from_json(claim, owner, deposit, block, description, &Value::Null)
+            }
+            Some([claim, owner, deposit, block, description, expires_at]) => {
+                claim_from_json(claim, owner, deposit, block, description, expires_at)
+            }
+            _ => None,
+        };
+        let claim = claim.ok_or(Error::Invalid("claims"))?;
+        // As reivindicações não podem ser de depois do bloco gênese, ter uma descrição longa
+        // demais, nem já ter expirado.
+        let max_length = <Runtime as proof_of_existence::Config>::MAX_DESCRIPTION_LENGTH;
+        if claim.1.block_number > block_number
+            || claim
+                .1
+                .description
+                .as_ref()
+                .is_some_and(|d| d.len() > max_length)
+            || claim
+                .1
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= block_number)
+        {
+            return Err(Error::Invalid("claims"));
+        }
+        // O mesmo conteúdo só pode ser reivindicado uma vez.
+        if !contents.insert(claim.0) {
+            return Err(Error::Invalid("claims"));
+        }
+        let count = claims_per_owner.entry(claim.1.owner).or_default();
+        *count += 1;
+        if *count > <Runtime as proof_of_existence::Config>::MAX_CLAIMS_PER_ACCOUNT {
+            return Err(Error::Invalid("claims"));
+        }
+        claims.push(claim);
+    }
+    Ok(claims)
+}
+
+fn claim_from_json(
+    claim: &Value,
+    owner: &Value,
+    deposit: &Value,
+    block_number: &Value,
+    description: &Value,
+    expires_at: &Value,
+) -> Option<(types::Content, types::ClaimInfo)> {
+    let description = match description {
+        Value::Null => None,
+        description => Some(bytes_from_json(description)?),
+    };
+    let expires_at = match expires_at {
+        Value::Null => None,
+        expires_at => Some(expires_at.as_u64()?.try_into().ok()?),
+    };
+    let info = types::ClaimInfo {
+        owner: account_from_json(owner)?,
+        deposit: balance_from_json(deposit)?,
+        block_number: block_number.as_u64()?.try_into().ok()?,
+        description,
+        expires_at,
+    };
+    Some((content_from_json(claim)?, info))
+}
+
+fn bytes_from_json(value: &Value) -> Option<Vec<u8>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|byte| byte.as_u64()?.try_into().ok())
+        .collect()
+}
+
+fn content_from_json(value: &Value) -> Option<types::Content> {
+    crypto::H256::from_hex(value.as_str()?)
+}
+
+/// JSON só representa inteiros de até 64 bits com precisão, então saldos maiores são guardados
+/// como strings.
+fn balance_to_json(balance: types::Balance) -> Value {
+    match u64::try_from(balance) {
+        Ok(balance) => json!(balance),
+        Err(_) => json!(balance.to_string()),
+    }
+}
+
+fn balance_from_json(value: &Value) -> Option<types::Balance> {
+    match value {
+        Value::Number(number) => number.as_u64().map(Into::into),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainSpec, Error};
+    use crate::crypto::Pair;
+    use crate::{balances, proof_of_existence, types, Runtime};
+
+    #[test]
+    fn json_round_trip() {
+        let alice = Pair::from_name("alice").public();
+        let bob = Pair::from_name("bob").public();
+        let mut spec = ChainSpec::development();
+        spec.genesis.system.block_number = 7;
+        spec.genesis.balances.balances.push((bob, u128::MAX - 205));
+        spec.genesis.balances.reserved.push((alice, 5));
+        spec.genesis.balances.reserved.push((bob, 100));
+        let lock = balances::BalanceLock {
+            id: *b"vesting ",
+            amount: 50,
+            reasons: balances::Reasons::All,
+        };
+        spec.genesis.balances.locks.push((alice, lock.clone()));
+        let claim = types::ClaimInfo {
+            owner: alice,
+            deposit: 5,
+            block_number: 3,
+            description: Some(b"Red".to_vec()),
+            expires_at: Some(20),
+        };
+        let mustang = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+        spec.genesis.proof_of_existence.claims = vec![(mustang, claim.clone())];
+        spec.genesis.proof_of_existence.pending_transfers = vec![(mustang, bob)];
+
+        let json = spec.to_json();
+        let decoded = ChainSpec::from_json(&json).unwrap();
+        assert_eq!(decoded.to_json(), json);
+
+        let runtime = decoded.build();
+        assert_eq!(runtime.system.block_number(), 7);
+        assert_eq!(runtime.balances.balance(&alice), 100);
+        assert_eq!(runtime.balances.balance(&bob), u128::MAX - 205);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 5);
+        assert_eq!(runtime.balances.reserved_balance(&bob), 100);
+        assert_eq!(runtime.balances.locks(&alice), &[lock]);
+        assert_eq!(
+            runtime.proof_of_existence.claim_info(&mustang),
+            Some(&claim)
+        );
+        assert_eq!(
+            runtime.proof_of_existence.pending_transfer(&mustang),
+            Some(&bob)
+        );
+    }
+
+    #[test]
+    fn export_runtime_state() {
+        let alice = Pair::from_name("alice").public();
+        let mut runtime = ChainSpec::development().build();
+        runtime.system.inc_block_number();
+        runtime.balances.set_balance(&alice, 42).unwrap();
+
+        let fork = ChainSpec::from_json(&ChainSpec::from_runtime("Fork", &runtime).to_json())
+            .unwrap()
+            .build();
+        assert_eq!(fork.system.block_number(), 1);
+        assert_eq!(fork.balances.balance(&alice), 42);
+        // O fork é uma nova cadeia, com o seu próprio bloco gênese.
+        assert_ne!(fork.system.genesis_hash(), runtime.system.genesis_hash());
+    }
+
+    #[test]
+    fn invalid_specs() {
+        let spec = |genesis: &str| {
+            ChainSpec::from_json(&format!(r#"{{"name": "Test", "genesis": {}}}"#, genesis))
+        };
+        let alice = Pair::from_name("alice").public().to_hex();
+        let mustang = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car").to_hex();
+
+        assert!(matches!(ChainSpec::from_json("{"), Err(Error::Json(_))));
+        assert!(matches!(spec("{}"), Err(Error::Missing("system"))));
+        assert!(matches!(
+            spec(r#"{"system": {"block_number": -1}}"#),
+            Err(Error::Invalid("block_number"))
+        ));
+
+        let genesis = |balances: &str, claims: &str| {
+            spec(&format!(
+                r#"{{
+                    "system": {{"block_number": 0}},
+                    "balances": {{"balances": {}}},
+                    "proof_of_existence": {{"claims": {}}}
+                }}"#,
+                balances, claims
+            ))
+        };
+        assert!(genesis("[]", "[]").is_ok());
+        assert!(genesis(&format!(r#"[["{}", "100"]]"#, alice), "[]").is_ok());
+        assert!(matches!(
+            genesis(r#"[["alice", 100]]"#, "[]"),
+            Err(Error::Invalid("balances"))
+        ));
+        assert!(matches!(
+            genesis(&format!(r#"[["{}", 100, 1]]"#, alice), "[]"),
+            Err(Error::Invalid("balances"))
+        ));
+        // Especificações mais antigas, sem saldos reservados nem bloqueios, continuam válidas.
+        let balances = |extra: &str| {
+            spec(&format!(
+                r#"{{
+                    "system": {{"block_number": 0}},
+                    "balances": {{"balances": [["{}", 100]], {}}},
+                    "proof_of_existence": {{"claims": []}}
+                }}"#,
+                alice, extra
+            ))
+        };
+        let reserved = |who: &str| format!(r#""reserved": [["{}", 10]]"#, who);
+        let lock = |who: &str, id: &str| format!(r#"["{}", {}, 1, "Fee"]"#, who, id);
+        let locks = |locks: &[String]| format!(r#""locks": [{}]"#, locks.join(", "));
+        let id = "[0, 0, 0, 0, 0, 0, 0, 0]";
+        assert!(balances(&reserved(&alice)).is_ok());
+        assert!(balances(&locks(&[lock(&alice, id)])).is_ok());
+        // Só contas com um saldo livre podem ter fundos reservados ou bloqueios.
+        let bob = Pair::from_name("bob").public().to_hex();
+        assert!(matches!(
+            balances(&reserved(&bob)),
+            Err(Error::Invalid("reserved"))
+        ));
+        assert!(matches!(
+            balances(&locks(&[lock(&bob, id)])),
+            Err(Error::Invalid("locks"))
+        ));
+        // Os nomes dos bloqueios têm 8 bytes, e são únicos em cada conta.
+        assert!(matches!(
+            balances(&locks(&[lock(&alice, "[0]")])),
+            Err(Error::Invalid("locks"))
+        ));
+        assert!(matches!(
+            balances(&locks(&[lock(&alice, id), lock(&alice, id)])),
+            Err(Error::Invalid("locks"))
+        ));
+
+        // Abaixo do depósito existencial.
+        assert!(matches!(
+            genesis(&format!(r#"[["{}", 1]]"#, alice), "[]"),
+            Err(Error::Invalid("balances"))
+        ));
+        // Uma conta só pode receber um saldo.
+        assert!(matches!(
+            genesis(&format!(r#"[["{}", 100], ["{}", 50]]"#, alice, alice), "[]"),
+            Err(Error::Invalid("balances"))
+        ));
+        assert!(matches!(
+            balances(&format!(
+                r#""reserved": [["{}", 10], ["{}", 10]]"#,
+                alice, alice
+            )),
+            Err(Error::Invalid("reserved"))
+        ));
+        // O total emitido daria overflow.
+        assert!(matches!(
+            genesis(
+                &format!(r#"[["{}", "{}"], ["{}", 1]]"#, alice, u128::MAX, bob),
+                "[]"
+            ),
+            Err(Error::Invalid("balances"))
+        ));
+        // O mesmo conteúdo só pode ser reivindicado uma vez.
+        assert!(matches!(
+            genesis(
+                "[]",
+                &format!(r#"[["{0}", "{1}"], ["{0}", "{1}"]]"#, mustang, alice)
+            ),
+            Err(Error::Invalid("claims"))
+        ));
+        // Reivindicações de antes dos depósitos não têm depósito, mas as novas devem tê-lo
+        // reservado.
+        let endowed = format!(r#"[["{}", 100]]"#, alice);
+        assert!(genesis(&endowed, &format!(r#"[["{}", "{}"]]"#, mustang, alice)).is_ok());
+        assert!(matches!(
+            genesis(&endowed, &format!(r#"[["{}", "{}", 5]]"#, mustang, alice)),
+            Err(Error::Invalid("claims"))
+        ));
+        // As reivindicações não podem ser de depois do bloco gênese, nem ter uma descrição longa
+        // demais.
+        let claim = |block: u32, description: &str| {
+            format!(
+                r#"[["{}", "{}", 0, {}, {}]]"#,
+                mustang, alice, block, description
+            )
+        };
+        assert!(genesis(&endowed, &claim(0, "[82, 101, 100]")).is_ok());
+        assert!(genesis(&endowed, &claim(0, "null")).is_ok());
+        assert!(matches!(
+            genesis(&endowed, &claim(1, "null")),
+            Err(Error::Invalid("claims"))
+        ));
+        assert!(matches!(
+            genesis(&endowed, &claim(0, &format!("{:?}", [0; 65]))),
+            Err(Error::Invalid("claims"))
+        ));
+        // As reivindicações não podem ter expirado até o bloco gênese.
+        let expiring = |expires_at: &str| {
+            format!(
+                r#"[["{}", "{}", 0, 0, null, {}]]"#,
+                mustang, alice, expires_at
+            )
+        };
+        assert!(genesis(&endowed, &expiring("1")).is_ok());
+        assert!(genesis(&endowed, &expiring("null")).is_ok());
+        assert!(matches!(
+            genesis(&endowed, &expiring("0")),
+            Err(Error::Invalid("claims"))
+        ));
+        // Uma conta não pode ter mais de `MAX_CLAIMS_PER_ACCOUNT` reivindicações.
+        let claims = |count: u8| {
+            let claims: Vec<_> = (0..count)
+                .map(|i| {
+                    let content = proof_of_existence::Pallet::<Runtime>::content_of(&[i]);
+                    format!(r#"["{}", "{}"]"#, content.to_hex(), alice)
+                })
+                .collect();
+            format!("[{}]", claims.join(", "))
+        };
+        assert!(genesis(&endowed, &claims(16)).is_ok());
+        assert!(matches!(
+            genesis(&endowed, &claims(17)),
+            Err(Error::Invalid("claims"))
+        ));
+        // Só reivindicações existentes podem ser transferidas.
+        let transfers = |claims: &str| {
+            spec(&format!(
+                r#"{{
+                    "system": {{"block_number": 0}},
+                    "balances": {{"balances": []}},
+                    "proof_of_existence": {{
+                        "claims": {},
+                        "pending_transfers": [["{}", "{}"]]
+                    }}
+                }}"#,
+                claims, mustang, bob
+            ))
+        };
+        assert!(transfers(&claim(0, "null")).is_ok());
+        assert!(matches!(
+            transfers("[]"),
+            Err(Error::Invalid("pending_transfers"))
+        ));
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+
+/// Uma codificação binária determinística dos nossos tipos, inspirada no SCALE.
+///
+/// Inteiros são codificados em little endian com tamanho fixo, e sequências (como `String` e `Vec`)
+/// são prefixadas pelo seu tamanho como um inteiro compacto (veja `Compact`). Enums são
+/// codificados como o índice da variante seguido dos seus campos. O mesmo valor sempre produz os
+/// mesmos bytes, o que nos permite assinar, calcular o hash e reexecutar dados codificados.
+pub trait Encode {
+    /// Adiciona a codificação de `self` ao final de `dest`.
+    fn encode_to(&self, dest: &mut Vec<u8>);
+
+    /// Retorna a codificação de `self`.
+    fn encode(&self) -> Vec<u8> {
+        let mut dest = Vec::new();
+        self.encode_to(&mut dest);
+        dest
+    }
+}
+
+/// O inverso de `Encode`: reconstrói um valor a partir dos seus bytes.
+pub trait Decode: Sized {
+    /// Lê um valor do início de `input`, avançando `input` para depois dos bytes lidos.
+    fn decode(input: &mut &[u8]) -> Result<Self, Error>;
+
+    /// Lê um valor que deve ocupar todos os bytes de `input`.
+    fn decode_all(mut input: &[u8]) -> Result<Self, Error> {
+        let value = Self::decode(&mut input)?;
+        if !input.is_empty() {
+            return Err(Error("input has trailing bytes"));
+        }
+        Ok(value)
+    }
+}
+
+/// O erro retornado quando os bytes não representam um valor válido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error(pub &'static str);
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Codec error: {}", self.0)
+    }
+}
+
+/// Lê exatamente `len` bytes do início de `input`.
+fn read_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if input.len() < len {
+        return Err(Error("not enough data"));
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+macro_rules! impl_codec_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl Encode for $t {
+                fn encode_to(&self, dest: &mut Vec<u8>) {
+                    dest.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl Decode for $t {
+                fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+                    let bytes = read_bytes(input, core::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().expect("read the exact size; qed")))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_int!(u8, u16, u32, u64, u128);
+
+/// Um inteiro sem sinal codificado de forma compacta, usando menos bytes para valores pequenos.
+///
+/// Os dois bits menos significativos do primeiro byte indicam o modo:
+/// - `0b00`: um byte, para valores até `2^6 - 1`.
+/// - `0b01`: dois bytes, para valores até `2^14 - 1`.
+/// - `0b10`: quatro bytes, para valores até `2^30 - 1`.
+/// - `0b11`: os seis bits restantes indicam quantos bytes (menos 4) seguem com o valor.
+///
+/// Cada valor tem uma única codificação válida: a mais curta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compact(pub u128);
+
+impl Encode for Compact {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        let value = self.0;
+        if value < 1 << 6 {
+            dest.push((value as u8) << 2);
+        } else if value < 1 << 14 {
+            dest.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes());
+        } else if value < 1 << 30 {
+            dest.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes());
+        } else {
+            let bytes = value.to_le_bytes();
+            let len = bytes
+                .iter()
+                .rposition(|byte| *byte != 0)
+                .map_or(0, |i| i + 1)
+                .max(4);
+            dest.push((((len - 4) as u8) << 2) | 0b11);
+            dest.extend_from_slice(&bytes[..len]);
+        }
+    }
+}
+
+impl Decode for Compact {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        let first = u8::decode(input)?;
+        let value = match first & 0b11 {
+            0b00 => (first >> 2) as u128,
+            0b01 => {
+                let second = u8::decode(input)?;
+                let value = (u16::from_le_bytes([first, second]) >> 2) as u128;
+                if value < 1 << 6 {
+                    return Err(Error("compact integer is not in canonical form"));
+                }
+                value
+            }
+            0b10 => {
+                let rest = read_bytes(input, 3)?;
+                let value = (u32::from_le_bytes([first, rest[0], rest[1], rest[2]]) >> 2) as u128;
+                if value < 1 << 14 {
+                    return Err(Error("compact integer is not in canonical form"));
+                }
+                value
+            }
+            _ => {
+                let len = (first >> 2) as usize + 4;
+                if len > 16 {
+                    return Err(Error("compact integer is too large"));
+                }
+                let mut bytes = [0u8; 16];
+                bytes[..len].copy_from_slice(read_bytes(input, len)?);
+                let value = u128::from_le_bytes(bytes);
+                if value < 1 << 30 || bytes[len - 1] == 0 {
+                    return Err(Error("compact integer is not in canonical form"));
+                }
+                value
+            }
+        };
+        Ok(Compact(value))
+    }
+}
+
+/// Lê o prefixo de tamanho de uma sequência.
+fn decode_len(input: &mut &[u8]) -> Result<usize, Error> {
+    let Compact(len) = Compact::decode(input)?;
+    usize::try_from(len).map_err(|_| Error("length is too large"))
+}
+
+impl Encode for bool {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error("invalid bool")),
+        }
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        Compact(self.len() as u128).encode_to(dest);
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_slice().encode_to(dest);
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        // Nunca reservamos mais espaço do que os bytes restantes, para que um prefixo de tamanho
+        // malicioso não cause uma alocação enorme.
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl Encode for str {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        Compact(self.len() as u128).encode_to(dest);
+        dest.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_str().encode_to(dest);
+    }
+}
+
+impl Decode for String {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        let bytes = read_bytes(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error("invalid utf8"))
+    }
+}
+
+/// Arrays têm tamanho fixo, então não precisam de um prefixo de tamanho.
+impl<const N: usize> Encode for [u8; N] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.extend_from_slice(self);
+    }
+}
+
+impl<const N: usize> Decode for [u8; N] {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(read_bytes(input, N)?
+            .try_into()
+            .expect("read the exact size; qed"))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        match self {
+            None => dest.push(0),
+            Some(value) => {
+                dest.push(1);
+                value.encode_to(dest);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        match u8::decode(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(input)?)),
+            _ => Err(Error("invalid option")),
+        }
+    }
+}
+
+/// Mapas são codificados como uma sequência de pares `(chave, valor)`, em ordem crescente de chave.
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        Compact(self.len() as u128).encode_to(dest);
+        for (key, value) in self {
+            key.encode_to(dest);
+            value.encode_to(dest);
+        }
+    }
+}
+
+/// Para que a codificação seja única, as chaves devem estar em ordem estritamente crescente.
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+        let len = decode_len(input)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::decode(input)?;
+            if map.last_key_value().is_some_and(|(last, _)| last >= &key) {
+                return Err(Error("map keys are not sorted"));
+            }
+            let value = V::decode(input)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+/// A unidade não tem dados, então sua codificação é vazia.
+impl Encode for () {
+    fn encode_to(&self, _dest: &mut Vec<u8>) {}
+}
+
+impl Decode for () {
+    fn decode(_input: &mut &[u8]) -> Result<Self, Error> {
+        Ok(())
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+macro_rules! impl_codec_for_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($name: Encode),*> Encode for ($($name,)*) {
+            fn encode_to(&self, dest: &mut Vec<u8>) {
+                let ($($name,)*) = self;
+                $( $name.encode_to(dest); )*
+            }
+        }
+
+        impl<$($name: Decode),*> Decode for ($($name,)*) {
+            fn decode(input: &mut &[u8]) -> Result<Self, Error> {
+                Ok(($($name::decode(input)?,)*))
+            }
+        }
+    };
+}
+
+impl_codec_for_tuple!(A, B);
+impl_codec_for_tuple!(A, B, C);
+impl_codec_for_tuple!(A, B, C, D);
+impl_codec_for_tuple!(A, B, C, D, E);
+
+#[cfg(test)]
+mod tests {
+    use super::{Compact, Decode, Encode, Error};
+    use std::collections::BTreeMap;
+
+    fn round_trip<T: Encode + Decode + PartialEq + core::fmt::Debug>(value: T) {
+        assert_eq!(T::decode_all(&value.encode()), Ok(value));
+    }
+
+    #[test]
+    fn encode_primitives() {
+        assert_eq!(1u32.encode(), vec![1, 0, 0, 0]);
+        assert_eq!(true.encode(), vec![1]);
+        assert_eq!("ab".encode(), vec![8, b'a', b'b']);
+        assert_eq!(vec![1u8, 2].encode(), vec![8, 1, 2]);
+        assert_eq!([7u8; 2].encode(), vec![7, 7]);
+        assert_eq!(Some(1u8).encode(), vec![1, 1]);
+        assert_eq!(None::<u8>.encode(), vec![0]);
+        assert_eq!((1u8, 2u16).encode(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn decode_primitives() {
+        round_trip(u128::MAX);
+        round_trip(false);
+        round_trip("hello".to_string());
+        round_trip(vec![1u32, 2, 3]);
+        round_trip([9u8; 32]);
+        round_trip(Some(5u64));
+        round_trip((1u8, "a".to_string(), vec![true]));
+        round_trip(BTreeMap::from([(1u32, 10u64), (2, 20)]));
+
+        assert_eq!(u32::decode_all(&[1, 0]), Err(Error("not enough data")));
+        assert_eq!(
+            u8::decode_all(&[1, 0]),
+            Err(Error("input has trailing bytes"))
+        );
+        assert_eq!(bool::decode_all(&[2]), Err(Error("invalid bool")));
+        assert_eq!(
+            BTreeMap::<u8, u8>::decode_all(&[8, 2, 0, 1, 0]),
+            Err(Error("map keys are not sorted"))
+        );
+    }
+
+    #[test]
+    fn compact_integers() {
+        assert_eq!(Compact(0).encode(), vec![0x00]);
+        assert_eq!(Compact(1).encode(), vec![0x04]);
+        assert_eq!(Compact(63).encode(), vec![0xfc]);
+        assert_eq!(Compact(64).encode(), vec![0x01, 0x01]);
+        assert_eq!(Compact(16383).encode(), vec![0xfd, 0xff]);
+        assert_eq!(Compact(16384).encode(), vec![0x02, 0x00, 0x01, 0x00]);
+        assert_eq!(
+            Compact(1 << 30).encode(),
+            vec![0x03, 0x00, 0x00, 0x00, 0x40]
+        );
+        assert_eq!(Compact(u128::MAX).encode().len(), 17);
+
+        for value in [
+            0,
+            1,
+            63,
+            64,
+            16383,
+            16384,
+            (1 << 30) - 1,
+            1 << 30,
+            u64::MAX as u128,
+            u128::MAX,
+        ] {
+            round_trip(Compact(value));
+        }
+
+        // O valor 1 deve ser codificado com um único byte.
+        assert_eq!(
+            Compact::decode_all(&[0x05, 0x00]),
+            Err(Error("compact integer is not in canonical form"))
+        );
+    }
+}
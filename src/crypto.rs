@@ -0,0 +1,219 @@
+use crate::codec::{Decode, Encode, Error as CodecError};
+use crate::support::{Hasher, Verify};
+use ed25519_dalek::{Signer, Verifier};
+use sha2::Digest;
+
+/// Calcula o hash SHA-256 de `data`.
+pub fn hash(data: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(data).into()
+}
+
+/// Um hash de 32 bytes, como os calculados por `Sha256`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct H256(pub [u8; 32]);
+
+impl H256 {
+    /// O hash em hexadecimal, com o prefixo `0x`.
+    pub fn to_hex(self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// O inverso de `to_hex`.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        from_hex(hex).map(H256)
+    }
+}
+
+/// A função de hash SHA-256, implementada em Rust puro.
+pub struct Sha256;
+
+impl Hasher for Sha256 {
+    type Output = H256;
+
+    fn hash(data: &[u8]) -> H256 {
+        H256(hash(data))
+    }
+}
+
+/// Uma chave pública ed25519. É usada como o identificador de uma conta.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Public(pub [u8; 32]);
+
+impl Public {
+    /// A chave em hexadecimal, com o prefixo `0x`.
+    pub fn to_hex(self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// O inverso de `to_hex`.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        from_hex(hex).map(Public)
+    }
+}
+
+fn to_hex(bytes: &[u8; 32]) -> String {
+    let mut hex = String::from("0x");
+    for byte in bytes {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+    hex
+}
+
+/// Retorna `None` se `hex` não tem o prefixo `0x` seguido de exatamente 32 bytes em hexadecimal.
+fn from_hex(hex: &str) -> Option<[u8; 32]> {
+    let digits = hex.strip_prefix("0x")?;
+    if digits.len() != 64 || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(digits.as_bytes().chunks(2)) {
+        let pair = core::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Uma assinatura ed25519.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Signature(pub [u8; 64]);
+
+/// Um par de chaves ed25519, usado fora da blockchain para assinar extrínsecos.
+#[derive(Clone)]
+pub struct Pair(ed25519_dalek::SigningKey);
+
+impl Pair {
+    /// Cria um par de chaves a partir de uma semente secreta de 32 bytes.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Pair(ed25519_dalek::SigningKey::from_bytes(seed))
+    }
+
+    /// Cria um par de chaves cuja semente é o hash de `name`.
+    /// Útil para contas de desenvolvimento como `alice` e `bob`, mas nunca para contas reais, já
+    /// que qualquer pessoa pode derivar a mesma chave.
+    pub fn from_name(name: &str) -> Self {
+        Self::from_seed(&hash(name.as_bytes()))
+    }
+
+    pub fn public(&self) -> Public {
+        Public(self.0.verifying_key().to_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(self.0.sign(message).to_bytes())
+    }
+}
+
+impl Verify for Signature {
+    type Signer = Public;
+
+    fn verify(&self, message: &[u8], signer: &Public) -> bool {
+        let Ok(public) = ed25519_dalek::VerifyingKey::from_bytes(&signer.0) else {
+            return false;
+        };
+        let signature = ed25519_dalek::Signature::from_bytes(&self.0);
+        public.verify(message, &signature).is_ok()
+    }
+}
+
+impl Encode for H256 {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Encode for Public {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Encode for Signature {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+    }
+}
+
+impl Decode for H256 {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(H256(Decode::decode(input)?))
+    }
+}
+
+impl Decode for Public {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Public(Decode::decode(input)?))
+    }
+}
+
+impl Decode for Signature {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Signature(Decode::decode(input)?))
+    }
+}
+
+impl core::fmt::Debug for H256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl core::fmt::Debug for Public {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl core::fmt::Debug for Signature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "0x")?;
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pair, Public, Sha256, H256};
+    use crate::support::{Hasher, Verify};
+
+    #[test]
+    fn sign_and_verify() {
+        let alice = Pair::from_name("alice");
+        let bob = Pair::from_name("bob");
+        assert_ne!(alice.public(), bob.public());
+        assert_eq!(alice.public(), Pair::from_name("alice").public());
+
+        let signature = alice.sign(b"hello");
+        assert!(signature.verify(b"hello", &alice.public()));
+        assert!(!signature.verify(b"goodbye", &alice.public()));
+        assert!(!signature.verify(b"hello", &bob.public()));
+    }
+
+    #[test]
+    fn public_hex() {
+        let alice = Pair::from_name("alice").public();
+        assert_eq!(Public::from_hex(&alice.to_hex()), Some(alice));
+        assert_eq!(
+            Public::from_hex(&format!("0x{}", "00".repeat(32))),
+            Some(Public([0; 32]))
+        );
+
+        assert_eq!(Public::from_hex(&alice.to_hex()[2..]), None);
+        assert_eq!(Public::from_hex("0x00"), None);
+        assert_eq!(Public::from_hex(&format!("0x{}", "zz".repeat(32))), None);
+    }
+
+    #[test]
+    fn sha256() {
+        // O vetor de teste do SHA-256 para "abc".
+        let hash = Sha256::hash(b"abc");
+        assert_eq!(
+            hash.to_hex(),
+            "0xba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(H256::from_hex(&hash.to_hex()), Some(hash));
+        assert_ne!(Sha256::hash(b"abd"), hash);
+    }
+}
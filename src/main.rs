@@ -1,22 +1,42 @@
 mod balances;
+mod chain_spec;
+mod codec;
+mod crypto;
+mod merkle;
+#[cfg(test)]
+mod mock;
 mod proof_of_existence;
 mod support;
 mod system;
+mod transaction_payment;
 
-use crate::support::Dispatch;
+use crate::codec::{Decode, Encode};
 
 // These are the concrete types we will use in our simple state machine.
 // Modules are configured for these types directly, and they satisfy all of our
 // trait requirements.
 mod types {
-	pub type AccountId = String;
+	pub type AccountId = crate::crypto::Public;
+	pub type Signature = crate::crypto::Signature;
 	pub type Balance = u128;
 	pub type BlockNumber = u32;
 	pub type Nonce = u32;
-	pub type Extrinsic = crate::support::Extrinsic<AccountId, crate::RuntimeCall>;
+	pub type Hash = crate::crypto::H256;
+	pub type Extrinsic = crate::support::Extrinsic<
+		AccountId,
+		crate::RuntimeCall,
+		Nonce,
+		BlockNumber,
+		Balance,
+		Signature,
+	>;
 	pub type Header = crate::support::Header<BlockNumber>;
 	pub type Block = crate::support::Block<Header, Extrinsic>;
-	pub type Content = &'static str;
+	pub type Content = Hash;
+	pub type ClaimInfo = crate::proof_of_existence::ClaimInfo<AccountId, Balance, BlockNumber>;
+	pub type ChargeTransaction = crate::transaction_payment::Pallet<crate::Runtime>;
+	pub type ExtrinsicReceipt = crate::support::ExtrinsicReceipt<Balance, crate::RuntimeEvent>;
+	pub type BlockReceipt = crate::support::BlockReceipt<BlockNumber, Balance, crate::RuntimeEvent>;
 }
 
 // These are all the calls which are exposed to the world.
@@ -24,114 +44,1154 @@ mod types {
 
 // This is our main Runtime.
 // It accumulates all of the different pallets we want to use.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[macros::runtime]
 pub struct Runtime {
 	system: system::Pallet<Self>,
 	balances: balances::Pallet<Self>,
 	proof_of_existence: proof_of_existence::Pallet<Self>,
+	transaction_payment: transaction_payment::Pallet<Self>,
 }
 
 impl system::Config for Runtime {
 	type AccountId = types::AccountId;
 	type BlockNumber = types::BlockNumber;
 	type Nonce = types::Nonce;
+	type Hash = types::Hash;
+	type Hashing = crypto::Sha256;
+	type RuntimeEvent = RuntimeEvent;
+	const BLOCK_HASH_COUNT: usize = 256;
+	const MAX_BLOCK_WEIGHT: support::Weight = 1_000_000;
 }
 
 impl balances::Config for Runtime {
 	type Balance = types::Balance;
+	const EXISTENTIAL_DEPOSIT: types::Balance = 10;
 }
 
 impl proof_of_existence::Config for Runtime {
 	type Content = types::Content;
+	type Currency = balances::Pallet<Runtime>;
+	const CLAIM_DEPOSIT: types::Balance = 5;
+	const MAX_DESCRIPTION_LENGTH: usize = 64;
+	const TWO_STEP_TRANSFERS: bool = true;
+	const MAX_CLAIMS_PER_ACCOUNT: usize = 16;
 }
 
+impl transaction_payment::Config for Runtime {
+	type Currency = balances::Pallet<Runtime>;
 
+	fn weight_to_fee(weight: support::Weight) -> types::Balance {
+		(weight / 5_000).into()
+	}
+
+	fn length_to_fee(length: usize) -> types::Balance {
+		(length / 100) as types::Balance
+	}
+}
+
+// Sign `call` with the key `pair`, creating an extrinsic which can be included in a block.
+// The `birth` block, given by its number and hash, identifies the chain the extrinsic is meant
+// for, and the extrinsic is valid for as long as the runtime keeps its hash.
+fn sign_extrinsic(
+	pair: &crypto::Pair,
+	nonce: types::Nonce,
+	call: RuntimeCall,
+	birth: (types::BlockNumber, [u8; 32]),
+) -> types::Extrinsic {
+	sign_extrinsic_with_tip(pair, nonce, call, 0, birth)
+}
+
+// Like `sign_extrinsic`, but the caller also pays `tip` on top of the fee.
+fn sign_extrinsic_with_tip(
+	pair: &crypto::Pair,
+	nonce: types::Nonce,
+	call: RuntimeCall,
+	tip: types::Balance,
+	(birth, birth_hash): (types::BlockNumber, [u8; 32]),
+) -> types::Extrinsic {
+	let caller = pair.public();
+	let mut extrinsic = support::Extrinsic { caller, nonce, birth, call, tip, signature: None };
+	extrinsic.signature = Some(pair.sign(&extrinsic.signing_payload(&birth_hash)));
+	extrinsic
+}
+
+// Build a block with `extrinsics` on top of the last imported block, and execute it. Returns the
+// state root of the block.
+// If there are any errors, our system panics, since we should not execute invalid blocks.
+fn import_block(runtime: &mut Runtime, extrinsics: Vec<types::Extrinsic>) -> [u8; 32] {
+	let block = runtime.build_block(extrinsics);
+	let state_root = block.header.state_root;
+	// Blocks are stored and shared as bytes. We execute the decoded block, just like a node
+	// importing it from disk or from the network would.
+	let encoded = block.encode();
+	let block = types::Block::decode_all(&encoded).expect("invalid block encoding");
+	let receipt = runtime.execute_block(block).expect("invalid block");
+	let block_number = receipt.block_number;
+	let block_hash = runtime.system.block_hash(block_number).expect("block was just imported");
+	println!("Block {} Hash: {:02x?}", block_number, block_hash);
+	// Sanity check that no tokens were created or destroyed without being accounted for.
+	runtime.balances.assert_total_issuance();
+
+	// Print the result of every extrinsic, and the events emitted while executing the block.
+	for (i, extrinsic) in receipt.extrinsics.iter().enumerate() {
+		let result = match &extrinsic.result {
+			Ok(()) => "Ok".to_string(),
+			Err(e) => format!("Error: {}", e),
+		};
+		println!(
+			"Block {} Extrinsic {}: {}, weight {}, fee {}, {} events",
+			block_number,
+			i,
+			result,
+			extrinsic.weight,
+			extrinsic.fee,
+			extrinsic.events.len()
+		);
+	}
+	for event in runtime.system.events() {
+		println!("Block {} Event: {:?}", block_number, event);
+	}
+	state_root
+}
+
+// Command line options:
+// - `--chain <file>`: start from the genesis state in a JSON chain spec file, instead of the
+//   development chain.
+// - `--export <file>`: after executing the blocks, export the state to a JSON chain spec file,
+//   which can be used to start a fork of the chain.
+// - `--claim <file>`: hash a local file, and have alice claim it in an extra block. Only the hash
+//   is sent to the chain.
+#[derive(Default)]
+struct Cli {
+	chain: Option<String>,
+	export: Option<String>,
+	claim: Option<String>,
+}
+
+impl Cli {
+	fn parse(mut args: impl Iterator<Item = String>) -> Self {
+		let mut cli = Cli::default();
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--chain" => cli.chain = Some(args.next().expect("`--chain` requires a file")),
+				"--export" => cli.export = Some(args.next().expect("`--export` requires a file")),
+				"--claim" => cli.claim = Some(args.next().expect("`--claim` requires a file")),
+				other => panic!("unknown argument: {}", other),
+			}
+		}
+		cli
+	}
+}
 
 fn main() {
+	let cli = Cli::parse(std::env::args().skip(1));
+
+	// Development accounts. The keys are derived from their names, so never use them for real.
+	let alice = crypto::Pair::from_name("alice");
+	let bob = crypto::Pair::from_name("bob");
+	let charlie = crypto::Pair::from_name("charlie");
+
 	// Create a new instance of the Runtime.
-	// It will instantiate with it all the modules it uses.
-	let mut runtime = Runtime::new();
-	let alice = "alice".to_string();
-	let bob = "bob".to_string();
-	let charlie = "charlie".to_string();
+	// It will instantiate with it all the modules it uses, starting from the genesis state of the
+	// chain spec. By default, this is the development chain, where alice has some initial balance.
+	let spec = match &cli.chain {
+		Some(path) => {
+			let json = std::fs::read_to_string(path).expect("can not read the chain spec");
+			chain_spec::ChainSpec::from_json(&json)
+				.unwrap_or_else(|e| panic!("invalid chain spec: {}", e))
+		},
+		None => chain_spec::ChainSpec::development(),
+	};
+	let chain_name = spec.name.clone();
+	println!("Chain: {}", chain_name);
+	let mut runtime = spec.build();
+	let birth = runtime.system.last_block();
 
-	// Initialize the system with some initial balance.
-	runtime.balances.set_balance(&alice, 100);
+	// Only the hashes of the claimed contents are sent to the chain.
+	let mustang = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+	let ferrari = proof_of_existence::Pallet::<Runtime>::content_of(b"Ferrari Car");
 
-	// Here are the extrinsics in our block.
+	// Here are the extrinsics in our blocks.
 	// You can add or remove these based on the modules and calls you have set up.
-	let block_1 = types::Block {
-		header: support::Header { block_number: 1 },
-		extrinsics: vec![
-			support::Extrinsic {
-				caller: alice.clone(),
-				call: RuntimeCall::balances(balances::Call::transfer {
-					to: bob.clone(),
-					amount: 20,
-				}),
-			},
-			support::Extrinsic {
-				caller: alice.clone(),
-				call: RuntimeCall::balances(balances::Call::transfer { to: charlie, amount: 20 }),
-			},
-		],
-	};
+	let block_1 = vec![
+		sign_extrinsic_with_tip(
+			&alice,
+			0,
+			RuntimeCall::balances(balances::Call::transfer { to: bob.public(), amount: 30 }),
+			1,
+			birth,
+		),
+		sign_extrinsic(
+			&alice,
+			1,
+			RuntimeCall::balances(balances::Call::transfer { to: charlie.public(), amount: 20 }),
+			birth,
+		),
+	];
 
-	let block_2 = types::Block {
-		header: support::Header { block_number: 2 },
-		extrinsics: vec![
-			support::Extrinsic {
-				caller: alice.clone(),
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: &"Mustang Car",
-				}),
+	let block_2 = vec![
+		sign_extrinsic(
+			&alice,
+			2,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: mustang,
+				description: None,
+				expires_at: None,
+			}),
+			birth,
+		),
+		sign_extrinsic(
+			&bob,
+			0,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: mustang,
+				description: None,
+				expires_at: None,
+			}),
+			birth,
+		),
+	];
+
+	let block_3 = vec![
+		sign_extrinsic(
+			&alice,
+			3,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
+				claim: mustang,
+			}),
+			birth,
+		),
+		sign_extrinsic(
+			&bob,
+			1,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: mustang,
+				description: None,
+				expires_at: None,
+			}),
+			birth,
+		),
+		sign_extrinsic(
+			&alice,
+			4,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: mustang,
+				description: None,
+				expires_at: None,
+			}),
+			birth,
+		),
+		sign_extrinsic(
+			&alice,
+			5,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: ferrari,
+				description: Some(b"Red, 1962".to_vec()),
+				expires_at: None,
+			}),
+			birth,
+		),
+	];
+
+	// Build and execute the blocks, each one on top of the previous one.
+	let mut state_root = [0; 32];
+	for extrinsics in [block_1, block_2, block_3] {
+		state_root = import_block(&mut runtime, extrinsics);
+	}
+	if let Some(path) = &cli.claim {
+		let data = std::fs::read(path).expect("can not read the file to claim");
+		let content = proof_of_existence::Pallet::<Runtime>::content_of(&data);
+		println!("Claiming {}: {:?}", path, content);
+		// Some extrinsics of alice may have been rejected, so her nonce is read from the state.
+		let nonce = runtime.system.account_nonce(&alice.public());
+		let extrinsic = sign_extrinsic(
+			&alice,
+			nonce,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: content,
+				description: None,
+				expires_at: None,
+			}),
+			birth,
+		);
+		state_root = import_block(&mut runtime, vec![extrinsic]);
+	}
+
+	// A light client which only knows the header of the last block can check values of the state
+	// with a storage proof, without downloading the whole state.
+	let bob_balance_key =
+		support::prefixed_key("balances", &balances::Pallet::<Runtime>::balance_key(&bob.public()));
+	if let Some(proof) = runtime.storage_proof(&bob_balance_key) {
+		assert!(proof.verify(&state_root), "invalid storage proof");
+		let balance = types::Balance::decode_all(&proof.value).expect("invalid balance encoding");
+		println!("Proved balance of bob: {}", balance);
+	}
+
+	let claim_key = support::prefixed_key(
+		"proof_of_existence",
+		&proof_of_existence::Pallet::<Runtime>::claim_key(&mustang),
+	);
+	if let Some(proof) = runtime.storage_proof(&claim_key) {
+		assert!(proof.verify(&state_root), "invalid storage proof");
+		let info = types::ClaimInfo::decode_all(&proof.value).expect("invalid claim encoding");
+		println!(
+			"Proved owner of \"Mustang Car\": {:?}, claimed in block {}",
+			info.owner, info.block_number
+		);
+	}
+
+	// Simply print the debug format of our runtime state.
+	println!("{:#?}", runtime);
+	println!("Total issuance: {}", runtime.balances.total_issuance());
+
+	if let Some(path) = &cli.export {
+		let spec = chain_spec::ChainSpec::from_runtime(&format!("{} Fork", chain_name), &runtime);
+		std::fs::write(path, spec.to_json()).expect("can not write the chain spec");
+		println!("Exported the state to {}", path);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// A runtime where only `who` has a balance.
+	fn endowed(who: &crypto::Pair, amount: types::Balance) -> Runtime {
+		Runtime::from_genesis(RuntimeGenesisConfig {
+			balances: balances::GenesisConfig {
+				balances: vec![(who.public(), amount)],
+				..Default::default()
 			},
-			support::Extrinsic {
-				caller: bob.clone(),
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: &"Mustang Car",
+			..Default::default()
+		})
+	}
+
+	fn transfer(to: &crypto::Pair, amount: types::Balance) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.public(), amount })
+	}
+
+	#[test]
+	fn block_encoding_round_trips() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let runtime = Runtime::from_genesis(Default::default());
+		let birth = runtime.system.last_block();
+		let claim = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car"),
+			description: Some(b"Red".to_vec()),
+			expires_at: None,
+		});
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&alice, 0, transfer(&bob, 20), birth),
+			sign_extrinsic(&bob, 0, claim, birth),
+		]);
+
+		let encoded = block.encode();
+		let decoded = types::Block::decode_all(&encoded).unwrap();
+		assert_eq!(decoded.encode(), encoded);
+
+		// Bytes which are not a valid block are rejected.
+		assert!(types::Block::decode_all(&encoded[..encoded.len() - 1]).is_err());
+		let mut invalid_call = encoded.clone();
+		// The pallet index of the first call, after the header, length, caller, nonce and birth.
+		invalid_call[32 + 4 + 32 + 32 + 1 + 32 + 4 + 4] = 99;
+		assert_eq!(
+			types::Block::decode_all(&invalid_call).err(),
+			Some(codec::Error("invalid pallet index"))
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_extrinsics() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+
+		// Unsigned.
+		let mut unsigned = sign_extrinsic(&alice, 0, transfer(&bob, 10), birth);
+		unsigned.signature = None;
+		// Signed by someone other than the caller.
+		let mut forged = sign_extrinsic(&bob, 0, transfer(&bob, 10), birth);
+		forged.caller = alice.public();
+		// Signed for another chain.
+		let other_chain = sign_extrinsic(&alice, 0, transfer(&bob, 10), (0, [0; 32]));
+		// Born in a block which is not known yet.
+		let unborn = sign_extrinsic(&alice, 0, transfer(&bob, 10), (1, birth.1));
+		// Valid, followed by a replay of itself and an extrinsic from the future.
+		let valid = sign_extrinsic(&alice, 0, transfer(&bob, 10), birth);
+		let replay = sign_extrinsic(&alice, 0, transfer(&bob, 10), birth);
+		let future = sign_extrinsic(&alice, 5, transfer(&bob, 10), birth);
+
+		let block = runtime
+			.build_block(vec![unsigned, forged, other_chain, unborn, valid, replay, future]);
+		let receipt = runtime.execute_block(block).unwrap();
+		assert_eq!(
+			receipt.extrinsics[3].result,
+			Err(support::InvalidTransaction::UnknownBirth.into())
+		);
+
+		// Only the valid extrinsic paid a fee.
+		assert_eq!(runtime.balances.balance(&alice.public()), 87);
+		assert_eq!(runtime.balances.balance(&bob.public()), 10);
+		assert_eq!(runtime.system.account_nonce(&alice.public()), 1);
+		assert_eq!(runtime.system.account_nonce(&bob.public()), 0);
+	}
+
+	#[test]
+	fn rejects_invalid_headers() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let extrinsics = || vec![sign_extrinsic(&alice, 0, transfer(&bob, 10), birth)];
+
+		// Not built on top of the last block.
+		let mut wrong_parent = runtime.build_block(extrinsics());
+		wrong_parent.header.parent_hash = [1; 32];
+		assert!(runtime.execute_block(wrong_parent).is_err());
+		// The extrinsics were changed after the header was built.
+		let mut wrong_extrinsics = runtime.build_block(extrinsics());
+		wrong_extrinsics.extrinsics.clear();
+		assert!(runtime.execute_block(wrong_extrinsics).is_err());
+		// The declared state is not the state reached by executing the block.
+		let mut wrong_state = runtime.build_block(extrinsics());
+		wrong_state.header.state_root = [1; 32];
+		assert!(runtime.execute_block(wrong_state).is_err());
+
+		// A rejected block leaves no changes behind, and building a block changes nothing either.
+		assert_eq!(runtime.system.block_number(), 0);
+		assert_eq!(runtime.system.last_block(), birth);
+		assert_eq!(runtime.balances.balance(&bob.public()), 0);
+
+		let block_1 = runtime.build_block(extrinsics());
+		let block_1_hash = block_1.header.hash();
+		runtime.execute_block(block_1).unwrap();
+		assert_eq!(runtime.system.last_block_hash(), block_1_hash);
+		assert_eq!(runtime.balances.balance(&bob.public()), 10);
+	}
+
+	#[test]
+	fn rejects_overweight_blocks() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let extrinsic = sign_extrinsic(&alice, 0, transfer(&bob, 10), birth);
+		let weight = extrinsic.call.weight();
+		let limit = <Runtime as system::Config>::MAX_BLOCK_WEIGHT;
+
+		// The weight of a block is known before executing it, so a block with more calls than fit
+		// in the limit is rejected before any of them is dispatched.
+		let mut block = runtime.build_block(vec![extrinsic.clone()]);
+		block.extrinsics = vec![extrinsic; (limit / weight) as usize + 1];
+		block.header.extrinsics_root = types::Block::extrinsics_root(&block.extrinsics);
+		assert_eq!(
+			runtime.execute_block(block).err(),
+			Some(support::DispatchError::Other("block weight exceeds the limit"))
+		);
+		assert_eq!(runtime.system.block_number(), 0);
+	}
+
+	#[test]
+	fn storage_proofs() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let claim = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&alice, 0, transfer(&bob, 20), birth),
+			sign_extrinsic(
+				&bob,
+				0,
+				RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+					claim,
+					description: None,
+					expires_at: None,
 				}),
-			},
-		],
-	};
+				birth,
+			),
+		]);
+		let state_root = block.header.state_root;
+		runtime.execute_block(block).unwrap();
+
+		let balance_key = support::prefixed_key(
+			"balances",
+			&balances::Pallet::<Runtime>::balance_key(&bob.public()),
+		);
+		let proof = runtime.storage_proof(&balance_key).unwrap();
+		assert!(proof.verify(&state_root));
+		// The deposit of the claim is reserved, and the fee for the claim is paid.
+		assert_eq!(types::Balance::decode_all(&proof.value), Ok(12));
+
+		let claim_key = support::prefixed_key(
+			"proof_of_existence",
+			&proof_of_existence::Pallet::<Runtime>::claim_key(&claim),
+		);
+		let proof = runtime.storage_proof(&claim_key).unwrap();
+		assert!(proof.verify(&state_root));
+		let info = types::ClaimInfo {
+			owner: bob.public(),
+			deposit: 5,
+			block_number: 1,
+			description: None,
+			expires_at: None,
+		};
+		assert_eq!(types::ClaimInfo::decode_all(&proof.value), Ok(info.clone()));
+		// Proofs can be shipped to a light client as bytes.
+		let proof = support::StorageProof::decode_all(&proof.encode()).unwrap();
+		assert!(proof.verify(&state_root));
+
+		// A proof does not hold for another value, or against another state root.
+		let mut forged = proof.clone();
+		forged.value = types::ClaimInfo { owner: alice.public(), ..info }.encode();
+		assert!(!forged.verify(&state_root));
+		assert!(!proof.verify(&runtime.system.genesis_hash()));
+
+		// Only stored values can be proven.
+		let charlie = crypto::Pair::from_name("charlie");
+		let missing = support::prefixed_key(
+			"balances",
+			&balances::Pallet::<Runtime>::balance_key(&charlie.public()),
+		);
+		assert_eq!(runtime.storage_proof(&missing), None);
+	}
+
+	#[test]
+	fn reaps_accounts() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+
+		// Leaves less than the existential deposit in the account of alice, once the fee is paid.
+		let reaping = sign_extrinsic(&alice, 0, transfer(&bob, 95), birth);
+		let block = runtime.build_block(vec![reaping.clone()]);
+		runtime.execute_block(block).unwrap();
+
+		assert_eq!(runtime.balances.balance(&alice.public()), 0);
+		assert_eq!(runtime.balances.balance(&bob.public()), 95);
+		assert_eq!(runtime.balances.total_issuance(), 95);
+		runtime.balances.assert_total_issuance();
+		// The system pallet forgot the account, even though its nonce was incremented by the
+		// extrinsic which reaped it.
+		assert_eq!(runtime.system.account_nonce(&alice.public()), 0);
+		let events = runtime.system.events();
+		assert!(events.iter().any(|event| matches!(
+			event,
+			RuntimeEvent::balances(balances::Event::KilledAccount { dust: 1, .. })
+		)));
+		// The refund for an account which was reaped is lost.
+		assert!(matches!(
+			events.last(),
+			Some(RuntimeEvent::transaction_payment(
+				transaction_payment::Event::TransactionFeePaid { actual_fee: 4, .. }
+			))
+		));
+
+		// Once the account is recreated, the extrinsic which reaped it can not be replayed, since
+		// it was born before the account was reaped. Extrinsics born since then are valid.
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&bob, 0, transfer(&alice, 50), birth),
+			reaping,
+			sign_extrinsic(&alice, 0, transfer(&bob, 10), runtime.system.last_block()),
+		]);
+		let receipt = runtime.execute_block(block).unwrap();
+		assert_eq!(receipt.extrinsics[1].result, Err(support::InvalidTransaction::Stale.into()));
+		assert_eq!(receipt.extrinsics[2].result, Ok(()));
+		assert_eq!(runtime.system.account_nonce(&alice.public()), 1);
+	}
+
+	#[test]
+	fn transaction_fees() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let charlie = crypto::Pair::from_name("charlie");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let block = runtime.build_block(vec![
+			// Pays a tip, and gets back the fee for the weight it did not use.
+			sign_extrinsic_with_tip(&alice, 0, transfer(&bob, 10), 5, birth),
+			// Fails, but still pays the fee for its full weight.
+			sign_extrinsic(&alice, 1, transfer(&bob, 1_000), birth),
+			// Can not pay the fee, so it is not dispatched.
+			sign_extrinsic(&charlie, 0, transfer(&bob, 0), birth),
+		]);
+		runtime.execute_block(block).unwrap();
+
+		assert_eq!(runtime.balances.balance(&alice.public()), 78);
+		assert_eq!(runtime.balances.balance(&bob.public()), 10);
+		assert_eq!(runtime.balances.total_issuance(), 88);
+		runtime.balances.assert_total_issuance();
+		assert_eq!(runtime.system.account_nonce(&alice.public()), 2);
+		assert_eq!(runtime.system.account_nonce(&charlie.public()), 0);
+		let fees: Vec<_> = runtime
+			.system
+			.events()
+			.iter()
+			.filter_map(|event| match event {
+				RuntimeEvent::transaction_payment(
+					transaction_payment::Event::TransactionFeePaid { who, actual_fee, tip },
+				) => Some((*who, *actual_fee, *tip)),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(fees, vec![(alice.public(), 8, 5), (alice.public(), 4, 0)]);
+	}
+
+	#[test]
+	fn errors_keep_the_index_of_their_pallet() {
+		let alice = crypto::Pair::from_name("alice");
+		let mut runtime = endowed(&alice, 14);
+		let birth = runtime.system.last_block();
+		let claim = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car"),
+			description: None,
+			expires_at: None,
+		});
+
+		// Alice can pay the fee, but not the deposit of the claim. The error comes from balances,
+		// even though the call was dispatched to proof_of_existence.
+		let block = runtime.build_block(vec![sign_extrinsic(&alice, 0, claim, birth)]);
+		let receipt = runtime.execute_block(block).unwrap();
+		let error = support::DispatchError::from(balances::Error::BelowExistentialDeposit);
+		assert_eq!(receipt.extrinsics[0].result, Err(error.with_pallet_index(1)));
+	}
+
+	#[test]
+	fn block_receipts() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&alice, 0, transfer(&bob, 10), birth),
+			sign_extrinsic(&alice, 1, transfer(&bob, 1_000), birth),
+			sign_extrinsic(&alice, 1, transfer(&bob, 10), birth),
+		]);
+		let receipt = runtime.execute_block(block).unwrap();
+		assert_eq!(receipt.block_number, 1);
+		let [transferred, failed, rejected] = &receipt.extrinsics[..] else {
+			panic!("expected a receipt for every extrinsic");
+		};
+		let fee_paid = |events: &[RuntimeEvent]| {
+			matches!(
+				events.last(),
+				Some(RuntimeEvent::transaction_payment(
+					transaction_payment::Event::TransactionFeePaid { .. }
+				))
+			)
+		};
 
-	let block_3 = types::Block {
-		header: support::Header { block_number: 3 },
-		extrinsics: vec![
-			support::Extrinsic {
-				caller: alice.clone(),
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
-					claim: &"Mustang Car",
+		// The transfer did not reap alice, so it used less than its declared weight.
+		assert_eq!(transferred.result, Ok(()));
+		assert_eq!((transferred.weight, transferred.fee), (10_000, 3));
+		assert!(transferred.events.iter().any(|event| matches!(
+			event,
+			RuntimeEvent::balances(balances::Event::Transfer { amount: 10, .. })
+		)));
+		assert!(fee_paid(&transferred.events));
+
+		// The error of a failed call keeps the index of its pallet, and only the fee is left.
+		let error = support::DispatchError::from(balances::Error::InsufficientBalance);
+		assert_eq!(failed.result, Err(error.with_pallet_index(1)));
+		assert_eq!((failed.weight, failed.fee), (15_000, 4));
+		assert!(fee_paid(&failed.events));
+		assert!(!failed.events.iter().any(|event| matches!(
+			event,
+			RuntimeEvent::balances(balances::Event::Transfer { .. })
+		)));
+
+		// A rejected extrinsic is not dispatched and pays nothing.
+		let stale = support::DispatchError::from(support::InvalidTransaction::Stale);
+		assert_eq!(rejected.result, Err(stale));
+		assert_eq!((rejected.weight, rejected.fee), (0, 0));
+		assert!(rejected.events.is_empty());
+
+		// Every event of the extrinsics is also kept in the system pallet.
+		let events = transferred.events.len() + failed.events.len();
+		assert_eq!(runtime.system.events().len(), events);
+	}
+
+	#[test]
+	fn two_step_claim_transfers() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let charlie = crypto::Pair::from_name("charlie");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let claim = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+		let call = |call| RuntimeCall::proof_of_existence(call);
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&alice, 0, transfer(&bob, 20), birth),
+			sign_extrinsic(
+				&alice,
+				1,
+				call(proof_of_existence::Call::create_claim {
+					claim,
+					description: None,
+					expires_at: None,
 				}),
-			},
-			support::Extrinsic {
-				caller: bob,
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: &"Mustang Car",
+				birth,
+			),
+			sign_extrinsic(
+				&alice,
+				2,
+				call(proof_of_existence::Call::transfer_claim {
+					claim,
+					new_owner: bob.public(),
 				}),
+				birth,
+			),
+		]);
+		runtime.execute_block(block).unwrap();
+
+		// The claim is only offered to bob, and nobody else can accept it.
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), Some(&alice.public()));
+		assert_eq!(runtime.proof_of_existence.pending_transfer(&claim), Some(&bob.public()));
+		let accept = call(proof_of_existence::Call::accept_claim { claim });
+		let block = runtime.build_block(vec![
+			sign_extrinsic(&charlie, 0, accept.clone(), birth),
+			sign_extrinsic(&bob, 0, accept, birth),
+		]);
+		runtime.execute_block(block).unwrap();
+
+		// The deposit moved to the reserved balance of bob, along with the claim.
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), Some(&bob.public()));
+		assert_eq!(runtime.proof_of_existence.pending_transfer(&claim), None);
+		assert_eq!(runtime.balances.reserved_balance(&alice.public()), 0);
+		assert_eq!(runtime.balances.reserved_balance(&bob.public()), 5);
+		// Bob paid the fee for accepting the claim.
+		assert_eq!(runtime.balances.balance(&bob.public()), 17);
+		runtime.balances.assert_total_issuance();
+	}
+
+	#[test]
+	fn expiring_claims() {
+		let alice = crypto::Pair::from_name("alice");
+		let mut runtime = endowed(&alice, 100);
+		let birth = runtime.system.last_block();
+		let claim = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+		let create_claim = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim,
+			description: None,
+			expires_at: Some(2),
+		});
+		let block = runtime.build_block(vec![sign_extrinsic(&alice, 0, create_claim, birth)]);
+		runtime.execute_block(block).unwrap();
+		assert!(runtime.proof_of_existence.get_claim(&claim).is_some());
+
+		// The claim is removed at the end of its expiry block, which is part of the state root the
+		// block commits to.
+		let block = runtime.build_block(vec![]);
+		runtime.execute_block(block).unwrap();
+		assert!(runtime.proof_of_existence.get_claim(&claim).is_none());
+		assert_eq!(runtime.balances.reserved_balance(&alice.public()), 0);
+		assert!(runtime.system.events().iter().any(|event| matches!(
+			event,
+			RuntimeEvent::proof_of_existence(proof_of_existence::Event::ClaimExpired { .. })
+		)));
+	}
+
+	#[test]
+	fn genesis_config() {
+		let alice = crypto::Pair::from_name("alice");
+		let bob = crypto::Pair::from_name("bob");
+		let claim = proof_of_existence::Pallet::<Runtime>::content_of(b"Mustang Car");
+		let mut runtime = Runtime::from_genesis(RuntimeGenesisConfig {
+			system: system::GenesisConfig { block_number: 10 },
+			balances: balances::GenesisConfig {
+				balances: vec![(alice.public(), 100)],
+				..Default::default()
 			},
-            support::Extrinsic {
-				caller: alice.clone(),
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: &"Mustang Car",
-				}),
+			proof_of_existence: proof_of_existence::GenesisConfig {
+				claims: vec![(
+					claim,
+					types::ClaimInfo {
+						owner: bob.public(),
+						deposit: 0,
+						block_number: 10,
+						description: None,
+						expires_at: None,
+					},
+				)],
+				..Default::default()
 			},
-            support::Extrinsic {
-				caller: alice,
-				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: &"Ferrari Car",
-				}),
+			transaction_payment: Default::default(),
+		});
+		let genesis_hash = runtime.system.genesis_hash();
+		let birth = runtime.system.last_block();
+
+		assert_eq!(runtime.system.block_number(), 10);
+		assert_eq!(runtime.system.block_hash(10), Some(genesis_hash));
+		assert_eq!(runtime.balances.balance(&alice.public()), 100);
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), Some(&bob.public()));
+
+		// The genesis hash commits to the initial state, so chains with a different genesis config
+		// have a different genesis hash.
+		assert_ne!(genesis_hash, endowed(&alice, 100).system.genesis_hash());
+		assert_ne!(
+			endowed(&alice, 100).system.genesis_hash(),
+			endowed(&alice, 99).system.genesis_hash()
+		);
+
+		// The chain continues from the genesis block number.
+		let extrinsic = sign_extrinsic(&alice, 0, transfer(&bob, 10), birth);
+		let block = runtime.build_block(vec![extrinsic]);
+		assert_eq!(block.header.block_number, 11);
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.balances.balance(&bob.public()), 10);
+	}
+}
+
+// A runtime with two pallets which record when their hooks run, and have a call which writes to
+// several pallets before failing. Tests the order and the weights of the hooks, the rollback of
+// failed calls, and that a pallet can not be accessed through the runtime while it is taken out,
+// without depending on the pallets of the real runtime.
+#[cfg(test)]
+mod runtime_tests {
+	use crate::{balances, support, system, transaction_payment};
+	use std::cell::{Cell, RefCell};
+
+	thread_local! {
+		// Every hook called, in order, with the remaining weight given to `on_idle`.
+		static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+		// The weight returned by each `on_initialize` and each `on_idle`.
+		static INITIALIZE_WEIGHT: Cell<support::Weight> = const { Cell::new(0) };
+		static IDLE_WEIGHT: Cell<support::Weight> = const { Cell::new(0) };
+	}
+
+	fn take_calls() -> Vec<String> {
+		CALLS.with(|calls| calls.take())
+	}
+
+	macro_rules! test_pallet {
+		($name:ident) => {
+			mod $name {
+				use crate::codec::Encode;
+				use crate::support::{
+					storage_key, Currency, DispatchError, DispatchResult, HasPallet, Hooks,
+					ReservableCurrency, StorageEntry, Weight,
+				};
+				use core::marker::PhantomData;
+
+				pub trait Config:
+					crate::system::Config
+					+ HasPallet<crate::system::Pallet<Self>>
+					+ HasPallet<Pallet<Self>>
+					+ Sized
+				{
+					type Currency: ReservableCurrency<Self, AccountId = Self::AccountId>;
+				}
+
+				#[derive(Debug, Clone)]
+				pub enum Event<T: Config> {
+					Initialized { block_number: T::BlockNumber },
+					Written,
+				}
+
+				#[derive(Debug, Clone)]
+				pub struct GenesisConfig<T: Config>(PhantomData<T>);
+
+				impl<T: Config> Default for GenesisConfig<T> {
+					fn default() -> Self {
+						GenesisConfig(PhantomData)
+					}
+				}
+
+				#[derive(Debug, Clone)]
+				pub struct Pallet<T: Config> {
+					pub writes: u32,
+					events: Vec<Event<T>>,
+				}
+
+				#[macros::call]
+				impl<T: Config> Pallet<T> {
+					// Write to this pallet, to system and to balances, and then fail.
+					#[weight(100)]
+					pub fn write_and_fail(
+						&mut self,
+						runtime: &mut T,
+						caller: T::AccountId,
+					) -> DispatchResult {
+						self.writes += 1;
+						self.events.push(Event::Written);
+						let system: &mut crate::system::Pallet<T> = runtime.pallet_mut();
+						system.inc_nonce(&caller);
+						T::Currency::reserve(runtime, &caller, T::Currency::minimum_balance())?;
+						Err(DispatchError::Other("written and failed"))
+					}
+
+					// Read this pallet through the runtime, which panics since the pallet is taken
+					// out of the runtime while its call runs.
+					#[weight(100)]
+					pub fn read_itself(
+						&mut self,
+						runtime: &mut T,
+						_caller: T::AccountId,
+					) -> DispatchResult {
+						let pallet: &Pallet<T> = runtime.pallet();
+						self.writes = pallet.writes;
+						Ok(())
+					}
+				}
+
+				impl<T: Config> Pallet<T> {
+					pub fn new() -> Self {
+						Self {
+							writes: 0,
+							events: Vec::new(),
+						}
+					}
+
+					pub fn from_genesis(_config: GenesisConfig<T>) -> Self {
+						Self::new()
+					}
+
+					// Required by the runtime macro, but these tests never export the state.
+					#[allow(dead_code)]
+					pub fn genesis_config(&self) -> GenesisConfig<T> {
+						GenesisConfig::default()
+					}
+
+					pub fn take_events(&mut self) -> Vec<Event<T>> {
+						std::mem::take(&mut self.events)
+					}
+
+					pub fn storage_entries(&self) -> Vec<StorageEntry> {
+						vec![(storage_key("writes", &()), self.writes.encode())]
+					}
+				}
+
+				impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {
+					fn on_initialize(
+						&mut self,
+						_runtime: &mut T,
+						block_number: T::BlockNumber,
+					) -> Weight {
+						super::record(format!("{} on_initialize", stringify!($name)));
+						self.events.push(Event::Initialized { block_number });
+						super::INITIALIZE_WEIGHT.with(|weight| weight.get())
+					}
+
+					fn on_idle(&mut self, _runtime: &mut T, remaining_weight: Weight) -> Weight {
+						let name = stringify!($name);
+						super::record(format!("{} on_idle {}", name, remaining_weight));
+						super::IDLE_WEIGHT.with(|weight| weight.get())
+					}
+
+					fn on_finalize(&mut self, _runtime: &mut T, _block_number: T::BlockNumber) {
+						super::record(format!("{} on_finalize", stringify!($name)));
+					}
+				}
+			}
+		};
+	}
+
+	fn record(call: String) {
+		CALLS.with(|calls| calls.borrow_mut().push(call));
+	}
+
+	test_pallet!(first);
+	test_pallet!(second);
+
+	// The runtime macro expects these names, like in the real runtime.
+	mod types {
+		pub type AccountId = crate::crypto::Public;
+		pub type Balance = u128;
+		pub type BlockNumber = u32;
+		pub type Extrinsic = crate::support::Extrinsic<
+			AccountId,
+			super::RuntimeCall,
+			u32,
+			BlockNumber,
+			Balance,
+			crate::crypto::Signature,
+		>;
+		pub type Header = crate::support::Header<BlockNumber>;
+		pub type Block = crate::support::Block<Header, Extrinsic>;
+		pub type ChargeTransaction = crate::transaction_payment::Pallet<super::Runtime>;
+		pub type ExtrinsicReceipt = crate::support::ExtrinsicReceipt<Balance, super::RuntimeEvent>;
+		pub type BlockReceipt =
+			crate::support::BlockReceipt<BlockNumber, Balance, super::RuntimeEvent>;
+	}
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	pub struct Runtime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		transaction_payment: transaction_payment::Pallet<Self>,
+		first: first::Pallet<Self>,
+		second: second::Pallet<Self>,
+	}
+
+	impl system::Config for Runtime {
+		type AccountId = types::AccountId;
+		type BlockNumber = types::BlockNumber;
+		type Nonce = u32;
+		type Hash = crate::crypto::H256;
+		type Hashing = crate::crypto::Sha256;
+		type RuntimeEvent = RuntimeEvent;
+		const BLOCK_HASH_COUNT: usize = 1;
+		const MAX_BLOCK_WEIGHT: support::Weight = 1_000;
+	}
+
+	impl balances::Config for Runtime {
+		type Balance = types::Balance;
+		const EXISTENTIAL_DEPOSIT: types::Balance = 1;
+	}
+
+	impl transaction_payment::Config for Runtime {
+		type Currency = balances::Pallet<Runtime>;
+
+		fn weight_to_fee(_weight: support::Weight) -> types::Balance {
+			0
+		}
+
+		fn length_to_fee(_length: usize) -> types::Balance {
+			0
+		}
+	}
+
+	impl first::Config for Runtime {
+		type Currency = balances::Pallet<Runtime>;
+	}
+
+	impl second::Config for Runtime {
+		type Currency = balances::Pallet<Runtime>;
+	}
+
+	// Sign `call` with the key `pair`, as the first extrinsic of `pair`, born in the last block of
+	// `runtime`.
+	fn sign(runtime: &Runtime, pair: &crate::crypto::Pair, call: RuntimeCall) -> types::Extrinsic {
+		let (birth, birth_hash) = runtime.system.last_block();
+		let mut extrinsic = support::Extrinsic {
+			caller: pair.public(),
+			nonce: 0,
+			birth,
+			call,
+			tip: 0,
+			signature: None,
+		};
+		extrinsic.signature = Some(pair.sign(&extrinsic.signing_payload(&birth_hash)));
+		extrinsic
+	}
+
+	// Build and execute an empty block, returning the hooks called while executing it.
+	fn execute_empty_block(runtime: &mut Runtime) -> Result<Vec<String>, support::DispatchError> {
+		let block = runtime.build_block(vec![]);
+		take_calls();
+		runtime.execute_block(block)?;
+		Ok(take_calls())
+	}
+
+	#[test]
+	fn hooks_run_in_declaration_order() {
+		let mut runtime = Runtime::from_genesis(Default::default());
+		INITIALIZE_WEIGHT.with(|weight| weight.set(100));
+		IDLE_WEIGHT.with(|weight| weight.set(300));
+
+		// `on_idle` gets what is left of the block after `on_initialize` and every earlier
+		// `on_idle`.
+		assert_eq!(
+			execute_empty_block(&mut runtime),
+			Ok(vec![
+				"first on_initialize".to_string(),
+				"second on_initialize".to_string(),
+				"first on_idle 800".to_string(),
+				"second on_idle 500".to_string(),
+				"first on_finalize".to_string(),
+				"second on_finalize".to_string(),
+			])
+		);
+		// The events of the hooks are deposited like the events of calls.
+		let initialized = runtime
+			.system
+			.events()
+			.iter()
+			.filter(|event| {
+				matches!(
+					event,
+					RuntimeEvent::first(first::Event::Initialized { block_number: 1 })
+						| RuntimeEvent::second(second::Event::Initialized { block_number: 1 })
+				)
+			})
+			.count();
+		assert_eq!(initialized, 2);
+	}
+
+	#[test]
+	fn initialize_weight_counts_towards_the_limit() {
+		let mut runtime = Runtime::from_genesis(Default::default());
+
+		// The hooks can use the whole block, which leaves nothing for `on_idle`.
+		INITIALIZE_WEIGHT.with(|weight| weight.set(500));
+		let calls = execute_empty_block(&mut runtime).unwrap();
+		assert!(calls.contains(&"first on_idle 0".to_string()));
+
+		INITIALIZE_WEIGHT.with(|weight| weight.set(501));
+		assert_eq!(
+			execute_empty_block(&mut runtime),
+			Err(support::DispatchError::Other("block weight exceeds the limit"))
+		);
+		assert_eq!(runtime.system.block_number(), 1);
+	}
+
+	#[test]
+	fn failed_calls_roll_back_every_pallet() {
+		let alice = crate::crypto::Pair::from_name("alice");
+		let mut runtime = Runtime::from_genesis(RuntimeGenesisConfig {
+			balances: balances::GenesisConfig {
+				balances: vec![(alice.public(), 100)],
+				..Default::default()
 			},
-		],
-	};
+			..Default::default()
+		});
+		let extrinsic = sign(&runtime, &alice, RuntimeCall::first(first::Call::write_and_fail {}));
 
-	// Execute the extrinsics which make up our blocks.
-	// If there are any errors, our system panics, since we should not execute invalid blocks.
-	runtime.execute_block(block_1).expect("invalid block");
-	runtime.execute_block(block_2).expect("invalid block");
-	runtime.execute_block(block_3).expect("invalid block");
+		let block = runtime.build_block(vec![extrinsic]);
+		let receipt = runtime.execute_block(block).unwrap();
+		assert_eq!(
+			receipt.extrinsics[0].result,
+			Err(support::DispatchError::Other("written and failed"))
+		);
 
-	// Simply print the debug format of our runtime state.
-	println!("{:#?}", runtime);
-}
\ No newline at end of file
+		// Only the nonce incremented by the runtime is kept.
+		assert_eq!(runtime.first.writes, 0);
+		assert_eq!(runtime.system.account_nonce(&alice.public()), 1);
+		assert_eq!(runtime.balances.balance(&alice.public()), 100);
+		assert_eq!(runtime.balances.reserved_balance(&alice.public()), 0);
+		assert!(!runtime
+			.system
+			.events()
+			.iter()
+			.any(|event| matches!(event, RuntimeEvent::first(first::Event::Written))));
+	}
+
+	#[test]
+	#[should_panic(expected = "pallet `first` is taken out of the runtime while its call or hook runs")]
+	fn taken_pallets_can_not_be_accessed_through_the_runtime() {
+		let alice = crate::crypto::Pair::from_name("alice");
+		let runtime = Runtime::from_genesis(RuntimeGenesisConfig {
+			balances: balances::GenesisConfig {
+				balances: vec![(alice.public(), 100)],
+				..Default::default()
+			},
+			..Default::default()
+		});
+		let extrinsic = sign(&runtime, &alice, RuntimeCall::first(first::Call::read_itself {}));
+		let _ = runtime.build_block(vec![extrinsic]);
+	}
+
+	#[test]
+	#[should_panic(expected = "`on_idle` of `second` used more than the remaining weight")]
+	fn idle_weight_can_not_exceed_the_remaining_weight() {
+		let mut runtime = Runtime::from_genesis(Default::default());
+		// `first` leaves 400 of the 1_000, which is less than `second` uses.
+		IDLE_WEIGHT.with(|weight| weight.set(600));
+		let _ = execute_empty_block(&mut runtime);
+	}
+}
@@ -0,0 +1,177 @@
+use crate::codec::{Decode, Encode, Error as CodecError};
+use crate::crypto::hash;
+
+/// Calcula o hash de uma folha da árvore. O prefixo `0` distingue folhas de nós internos, para que
+/// um nó interno nunca possa ser apresentado como se fosse uma folha.
+pub fn hash_leaf(data: &[u8]) -> [u8; 32] {
+    let mut input = Vec::with_capacity(data.len() + 1);
+    input.push(0);
+    input.extend_from_slice(data);
+    hash(&input)
+}
+
+/// Calcula o hash de um nó interno a partir dos hashes dos seus dois filhos.
+pub fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut input = [0u8; 65];
+    input[0] = 1;
+    input[1..33].copy_from_slice(left);
+    input[33..].copy_from_slice(right);
+    hash(&input)
+}
+
+/// Calcula a raiz de uma árvore de merkle binária sobre `leaves`, na ordem dada.
+///
+/// A cada nível, os nós são combinados em pares. Quando um nível tem um número ímpar de nós, o
+/// último é promovido para o próximo nível sem alterações. A raiz de uma lista vazia é o hash de
+/// uma entrada vazia.
+pub fn root<Leaf: AsRef<[u8]>>(leaves: &[Leaf]) -> [u8; 32] {
+    let mut layer = leaves
+        .iter()
+        .map(|leaf| hash_leaf(leaf.as_ref()))
+        .collect::<Vec<_>>();
+    if layer.is_empty() {
+        return hash(&[]);
+    }
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks have one or two items; qed"),
+            })
+            .collect();
+    }
+    layer[0]
+}
+
+/// Uma prova de que uma folha está na posição `index` de uma árvore de merkle com `leaf_count`
+/// folhas. Contém os hashes irmãos no caminho da folha até a raiz, de baixo para cima.
+///
+/// O tamanho da prova é logarítmico no número de folhas, então quem verifica não precisa conhecer
+/// as outras folhas da árvore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub index: u32,
+    pub leaf_count: u32,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Gera a prova da folha na posição `index`, ou `None` se não existe folha nessa posição.
+pub fn prove<Leaf: AsRef<[u8]>>(leaves: &[Leaf], index: usize) -> Option<Proof> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut layer = leaves
+        .iter()
+        .map(|leaf| hash_leaf(leaf.as_ref()))
+        .collect::<Vec<_>>();
+    let mut position = index;
+    let mut siblings = Vec::new();
+    while layer.len() > 1 {
+        // Um nó promovido sem par não tem irmão neste nível.
+        let sibling = position ^ 1;
+        if sibling < layer.len() {
+            siblings.push(layer[sibling]);
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks have one or two items; qed"),
+            })
+            .collect();
+        position /= 2;
+    }
+    Some(Proof {
+        index: index as u32,
+        leaf_count: leaves.len() as u32,
+        siblings,
+    })
+}
+
+/// Verifica que `leaf` está na árvore com raiz `root`, na posição indicada por `proof`.
+pub fn verify(root: &[u8; 32], leaf: &[u8], proof: &Proof) -> bool {
+    if proof.index >= proof.leaf_count {
+        return false;
+    }
+    let mut hash = hash_leaf(leaf);
+    let mut position = proof.index;
+    let mut count = proof.leaf_count;
+    let mut siblings = proof.siblings.iter();
+    while count > 1 {
+        if position % 2 == 1 {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = hash_node(sibling, &hash);
+        } else if position + 1 < count {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = hash_node(&hash, sibling);
+        }
+        position /= 2;
+        count = count.div_ceil(2);
+    }
+    siblings.next().is_none() && &hash == root
+}
+
+impl Encode for Proof {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.index.encode_to(dest);
+        self.leaf_count.encode_to(dest);
+        self.siblings.encode_to(dest);
+    }
+}
+
+impl Decode for Proof {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Proof {
+            index: Decode::decode(input)?,
+            leaf_count: Decode::decode(input)?,
+            siblings: Decode::decode(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_leaf, hash_node, prove, root, verify};
+    use crate::crypto::hash;
+
+    #[test]
+    fn merkle_root() {
+        let [a, b, c] = [b"a", b"b", b"c"].map(|leaf| hash_leaf(leaf));
+
+        assert_eq!(root::<&[u8]>(&[]), hash(&[]));
+        assert_eq!(root(&[b"a"]), a);
+        assert_eq!(root(&[b"a", b"b"]), hash_node(&a, &b));
+        assert_eq!(root(&[b"a", b"b", b"c"]), hash_node(&hash_node(&a, &b), &c));
+
+        // A ordem das folhas importa.
+        assert_ne!(root(&[b"a", b"b"]), root(&[b"b", b"a"]));
+        // Uma folha nunca é confundida com um nó interno.
+        let node = [a, b].concat();
+        assert_ne!(root(&[node]), root(&[b"a", b"b"]));
+    }
+
+    #[test]
+    fn merkle_proofs() {
+        for count in 1..=7u8 {
+            let leaves = (0..count).map(|i| vec![i]).collect::<Vec<_>>();
+            let root = root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = prove(&leaves, index).unwrap();
+                assert!(verify(&root, leaf, &proof));
+                // A prova não vale para outra folha, nem para outra posição.
+                assert!(!verify(&root, &[count], &proof));
+                let mut moved = proof.clone();
+                moved.index = (moved.index + 1) % u32::from(count);
+                assert!(count == 1 || !verify(&root, leaf, &moved));
+            }
+            assert_eq!(prove(&leaves, count as usize), None);
+        }
+    }
+}
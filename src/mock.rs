@@ -0,0 +1,120 @@
+use crate::support::{HasPallet, Weight};
+use crate::{balances, proof_of_existence, system, transaction_payment};
+
+/// Um runtime escrito à mão para os testes dos pallets, com os pallets que se alcançam através do
+/// runtime: o pallet de sistema, os saldos, a prova de existência e o pagamento de transações.
+///
+/// Cada teste pode usar os pallets deste runtime, ou criar o seu próprio pallet e passar o runtime
+/// para as funções que o pedem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestRuntime {
+    pub system: system::Pallet<TestRuntime>,
+    pub balances: balances::Pallet<TestRuntime>,
+    pub proof_of_existence: proof_of_existence::Pallet<TestRuntime>,
+    pub transaction_payment: transaction_payment::Pallet<TestRuntime>,
+}
+
+impl TestRuntime {
+    /// Um runtime sem nenhuma conta.
+    pub fn new() -> Self {
+        Self::with_balances(Vec::new())
+    }
+
+    /// Um runtime onde cada conta de `balances` começa com o saldo livre dado.
+    pub fn with_balances(balances: Vec<(&'static str, u128)>) -> Self {
+        TestRuntime {
+            system: system::Pallet::new(),
+            balances: balances::Pallet::from_genesis(balances::GenesisConfig {
+                balances,
+                ..Default::default()
+            }),
+            proof_of_existence: proof_of_existence::Pallet::new(),
+            transaction_payment: transaction_payment::Pallet::new(),
+        }
+    }
+}
+
+impl HasPallet<system::Pallet<TestRuntime>> for TestRuntime {
+    const INDEX: u8 = 0;
+
+    fn pallet(&self) -> &system::Pallet<TestRuntime> {
+        &self.system
+    }
+
+    fn pallet_mut(&mut self) -> &mut system::Pallet<TestRuntime> {
+        &mut self.system
+    }
+}
+
+impl HasPallet<balances::Pallet<TestRuntime>> for TestRuntime {
+    const INDEX: u8 = 1;
+
+    fn pallet(&self) -> &balances::Pallet<TestRuntime> {
+        &self.balances
+    }
+
+    fn pallet_mut(&mut self) -> &mut balances::Pallet<TestRuntime> {
+        &mut self.balances
+    }
+}
+
+impl HasPallet<proof_of_existence::Pallet<TestRuntime>> for TestRuntime {
+    const INDEX: u8 = 2;
+
+    fn pallet(&self) -> &proof_of_existence::Pallet<TestRuntime> {
+        &self.proof_of_existence
+    }
+
+    fn pallet_mut(&mut self) -> &mut proof_of_existence::Pallet<TestRuntime> {
+        &mut self.proof_of_existence
+    }
+}
+
+impl HasPallet<transaction_payment::Pallet<TestRuntime>> for TestRuntime {
+    const INDEX: u8 = 3;
+
+    fn pallet(&self) -> &transaction_payment::Pallet<TestRuntime> {
+        &self.transaction_payment
+    }
+
+    fn pallet_mut(&mut self) -> &mut transaction_payment::Pallet<TestRuntime> {
+        &mut self.transaction_payment
+    }
+}
+
+impl system::Config for TestRuntime {
+    type AccountId = &'static str;
+    type BlockNumber = u32;
+    type Nonce = u32;
+    type Hash = crate::crypto::H256;
+    type Hashing = crate::crypto::Sha256;
+    type RuntimeEvent = ();
+    const BLOCK_HASH_COUNT: usize = 1;
+    const MAX_BLOCK_WEIGHT: Weight = u64::MAX;
+}
+
+impl balances::Config for TestRuntime {
+    type Balance = u128;
+    const EXISTENTIAL_DEPOSIT: u128 = 2;
+}
+
+impl proof_of_existence::Config for TestRuntime {
+    type Content = &'static str;
+    type Currency = balances::Pallet<TestRuntime>;
+    const CLAIM_DEPOSIT: u128 = 10;
+    const MAX_DESCRIPTION_LENGTH: usize = 8;
+    const TWO_STEP_TRANSFERS: bool = false;
+    const MAX_CLAIMS_PER_ACCOUNT: usize = 2;
+}
+
+impl transaction_payment::Config for TestRuntime {
+    type Currency = balances::Pallet<TestRuntime>;
+
+    fn weight_to_fee(weight: Weight) -> u128 {
+        (weight / 1_000).into()
+    }
+
+    fn length_to_fee(length: usize) -> u128 {
+        length as u128
+    }
+}
@@ -1,50 +1,325 @@
-use crate::support::DispatchResult;
+use crate::codec::{Decode, Encode};
+use crate::support::{
+    storage_key, ArithmeticError, BalanceStatus, Currency, DispatchResult, HasPallet, Hasher,
+    Hooks, ReservableCurrency, StorageEntry, Weight,
+};
 use core::fmt::Debug;
-use std::collections::BTreeMap;
+use num::traits::CheckedSub;
+use std::collections::{BTreeMap, BTreeSet};
 
-pub trait Config: crate::system::Config {
+pub trait Config: crate::system::Config + HasPallet<crate::system::Pallet<Self>> + Sized {
     /// O tipo que representa o conteúdo que pode ser reivindicado usando este pallet.
     /// Pode ser o conteúdo diretamente como bytes, ou melhor ainda, o hash desse conteúdo.
     /// Deixamos essa decisão para o desenvolvedor do runtime.
-    type Content: Debug + Ord;
+    type Content: Debug + Ord + Clone + Encode;
+    /// A moeda usada para cobrar e devolver fundos das contas, acessada através do runtime.
+    /// Normalmente é o `balances::Pallet` do runtime.
+    type Currency: ReservableCurrency<Self, AccountId = Self::AccountId>;
+    /// O depósito reservado do saldo de quem cria uma reivindicação, e devolvido quando ela é
+    /// revogada. Torna caro encher o armazenamento com reivindicações.
+    const CLAIM_DEPOSIT: BalanceOf<Self>;
+    /// O tamanho máximo, em bytes, da descrição de uma reivindicação.
+    const MAX_DESCRIPTION_LENGTH: usize;
+    /// Se verdadeiro, `transfer_claim` apenas oferece a reivindicação ao novo proprietário, que
+    /// precisa aceitá-la com `accept_claim`. Assim ninguém recebe uma reivindicação que não quer,
+    /// e uma transferência para a conta errada pode ser corrigida antes de ser aceita.
+    const TWO_STEP_TRANSFERS: bool;
+    /// O número máximo de reivindicações que uma conta pode ter ao mesmo tempo. Limita o tamanho
+    /// do índice de reivindicações de cada proprietário.
+    const MAX_CLAIMS_PER_ACCOUNT: usize;
+}
+
+/// O tipo de saldo da moeda usada por este pallet.
+pub type BalanceOf<T> = <<T as Config>::Currency as Currency<T>>::Balance;
+
+/// Os erros que as chamadas deste pallet podem retornar.
+#[macros::error]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Alguém já reivindicou este conteúdo.
+    ClaimAlreadyExists,
+    /// Não existe reivindicação para este conteúdo.
+    ClaimNotFound,
+    /// A reivindicação pertence a outra conta.
+    NotClaimOwner,
+    /// A descrição é maior que `MAX_DESCRIPTION_LENGTH`.
+    DescriptionTooLong,
+    /// O novo proprietário não tem uma conta que possa guardar o depósito da reivindicação.
+    NewOwnerDoesNotExist,
+    /// A reivindicação não foi oferecida ao chamador.
+    NoPendingTransfer,
+    /// A conta já tem `MAX_CLAIMS_PER_ACCOUNT` reivindicações.
+    TooManyClaims,
+    /// O bloco de expiração não está depois do bloco atual.
+    ExpiryInPast,
+}
+
+/// Os eventos emitidos por este pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// Uma nova reivindicação foi criada por `owner`.
+    ClaimCreated {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// Uma reivindicação foi revogada pelo seu proprietário.
+    ClaimRevoked {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// Uma reivindicação chegou ao seu bloco de expiração e foi removida.
+    ClaimExpired {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    /// O proprietário `from` ofereceu uma reivindicação a `to`, que ainda precisa aceitá-la.
+    TransferProposed {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: T::Content,
+    },
+    /// Uma reivindicação passou de `from` para `to`.
+    OwnerChanged {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: T::Content,
+    },
+}
+
+/// Tudo o que é guardado sobre uma reivindicação.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimInfo<AccountId, Balance, BlockNumber> {
+    /// A conta que fez a reivindicação.
+    pub owner: AccountId,
+    /// O depósito reservado do saldo do proprietário.
+    pub deposit: Balance,
+    /// O bloco em que a reivindicação foi criada, que prova que o conteúdo já existia nesse bloco.
+    /// Sem um pallet de timestamp, o número do bloco faz o papel da marca de tempo.
+    pub block_number: BlockNumber,
+    /// Uma descrição opcional do conteúdo, com no máximo `MAX_DESCRIPTION_LENGTH` bytes.
+    pub description: Option<Vec<u8>>,
+    /// O bloco ao final do qual a reivindicação é removida, se ela expirar.
+    pub expires_at: Option<BlockNumber>,
+}
+
+/// A `ClaimInfo` com os tipos de `T`.
+pub type ClaimInfoOf<T> = ClaimInfo<
+    <T as crate::system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as crate::system::Config>::BlockNumber,
+>;
+
+impl<AccountId: Encode, Balance: Encode, BlockNumber: Encode> Encode
+    for ClaimInfo<AccountId, Balance, BlockNumber>
+{
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (
+            &self.owner,
+            &self.deposit,
+            &self.block_number,
+            &self.description,
+            &self.expires_at,
+        )
+            .encode_to(dest);
+    }
+}
+
+impl<AccountId: Decode, Balance: Decode, BlockNumber: Decode> Decode
+    for ClaimInfo<AccountId, Balance, BlockNumber>
+{
+    fn decode(input: &mut &[u8]) -> Result<Self, crate::codec::Error> {
+        let (owner, deposit, block_number, description, expires_at) = Decode::decode(input)?;
+        Ok(ClaimInfo {
+            owner,
+            deposit,
+            block_number,
+            description,
+            expires_at,
+        })
+    }
+}
+
+/// O estado inicial deste pallet, usado para construir o bloco gênese.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config> {
+    /// Reivindicações que já existem no bloco gênese. O depósito de cada uma já deve estar
+    /// reservado no saldo do proprietário.
+    pub claims: Vec<(T::Content, ClaimInfoOf<T>)>,
+    /// Transferências oferecidas e ainda não aceitas, com o novo proprietário de cada
+    /// reivindicação.
+    pub pending_transfers: Vec<(T::Content, T::AccountId)>,
+}
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig {
+            claims: Vec::new(),
+            pending_transfers: Vec::new(),
+        }
+    }
 }
 
 /// Este é o Módulo de Prova de Existência.
 /// É um módulo simples que permite que contas reivindiquem a existência de alguns dados.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pallet<T: Config> {
-    /// Um simples mapa de armazenamento de conteúdo para o proprietário desse conteúdo.
+    /// Um simples mapa de armazenamento de conteúdo para o proprietário desse conteúdo, junto com
+    /// o depósito, o bloco em que foi reivindicado e a descrição.
     /// As contas podem fazer várias reivindicações diferentes, mas cada reivindicação só pode ter um proprietário.
-    claims: BTreeMap<T::Content, T::AccountId>,
+    claims: BTreeMap<T::Content, ClaimInfoOf<T>>,
+    /// As transferências oferecidas com `TWO_STEP_TRANSFERS`, com a conta que pode aceitar cada
+    /// uma. Só existem para reivindicações existentes.
+    pending_transfers: BTreeMap<T::Content, T::AccountId>,
+    /// O índice reverso de `claims`: as reivindicações de cada proprietário. Contas sem
+    /// reivindicações não aparecem no índice.
+    claims_of: BTreeMap<T::AccountId, BTreeSet<T::Content>>,
+    /// As reivindicações que expiram, ordenadas pelo bloco de expiração, para que o final de cada
+    /// bloco só visite as que expiram nele.
+    expiries: BTreeSet<(T::BlockNumber, T::Content)>,
+    /// Eventos emitidos pelas chamadas e ainda não coletados pelo runtime.
+    events: Vec<Event<T>>,
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
-    /// Cria uma nova reivindicação em nome do `caller`.
-    /// Esta função retornará um erro se alguém já tiver reivindicado esse conteúdo.
-    pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+    /// Cria uma nova reivindicação em nome do `caller`, reservando `CLAIM_DEPOSIT` do seu saldo.
+    /// A reivindicação guarda o bloco atual e uma descrição opcional. Com `expires_at`, ela é
+    /// removida ao final desse bloco, e o depósito é devolvido. O peso dessa remoção,
+    /// `EXPIRE_CLAIM_WEIGHT`, é cobrado já na criação.
+    /// Esta função retornará um erro se alguém já tiver reivindicado esse conteúdo, se a descrição
+    /// for longa demais, se a expiração não estiver depois do bloco atual, se o `caller` já tiver
+    /// `MAX_CLAIMS_PER_ACCOUNT` reivindicações, ou se não puder pagar o depósito.
+    #[weight(
+        10_000
+            + description.as_ref().map_or(0, |description| description.len() as u64)
+            + expires_at.map_or(0, |_| EXPIRE_CLAIM_WEIGHT)
+    )]
+    pub fn create_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        description: Option<Vec<u8>>,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
         if self.claims.contains_key(&claim) {
-            return Err("This claim already exists");
+            return Err(Error::ClaimAlreadyExists.into());
         }
+        if description
+            .as_ref()
+            .is_some_and(|description| description.len() > T::MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(Error::DescriptionTooLong.into());
+        }
+        let system: &crate::system::Pallet<T> = runtime.pallet();
+        let block_number = system.block_number();
+        if expires_at.is_some_and(|expires_at| expires_at <= block_number) {
+            return Err(Error::ExpiryInPast.into());
+        }
+        self.ensure_can_own(&caller)?;
 
-        self.claims.insert(claim, caller);
+        T::Currency::reserve(runtime, &caller, T::CLAIM_DEPOSIT)?;
+        let info = ClaimInfo {
+            owner: caller.clone(),
+            deposit: T::CLAIM_DEPOSIT,
+            block_number,
+            description,
+            expires_at,
+        };
+        if let Some(expires_at) = expires_at {
+            self.expiries.insert((expires_at, claim.clone()));
+        }
+        self.claims.insert(claim.clone(), info);
+        self.claims_of
+            .entry(caller.clone())
+            .or_default()
+            .insert(claim.clone());
+        self.deposit_event(Event::ClaimCreated {
+            owner: caller,
+            claim,
+        });
 
         Ok(())
     }
 
-    /// Revoga uma reivindicação existente em algum conteúdo.
+    /// Revoga uma reivindicação existente em algum conteúdo, devolvendo o depósito ao chamador.
     /// Esta função só deve ter sucesso se o chamador for o proprietário de uma reivindicação existente.
     /// Retornará um erro se a reivindicação não existir ou se o chamador não for o proprietário.
-    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        let owner = self.claims.get(&claim).ok_or("This claim does not exist")?;
-        if owner != &caller {
-            return Err("This claim does not belong to you");
+    #[weight(10_000)]
+    pub fn revoke_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        let info = self.claims.get(&claim).ok_or(Error::ClaimNotFound)?;
+        if info.owner != caller {
+            return Err(Error::NotClaimOwner.into());
+        }
+
+        self.remove_claim(runtime, &claim);
+        self.deposit_event(Event::ClaimRevoked {
+            owner: caller,
+            claim,
+        });
+
+        Ok(())
+    }
+
+    /// Transfere uma reivindicação do `caller` para `new_owner`, junto com o seu depósito, que
+    /// continua reservado no saldo de `new_owner`.
+    /// Com `TWO_STEP_TRANSFERS`, a reivindicação apenas é oferecida, e só muda de proprietário
+    /// quando `new_owner` a aceita com `accept_claim`. Uma nova oferta substitui a anterior, e
+    /// oferecer a reivindicação ao próprio `caller` cancela a oferta.
+    /// Retornará um erro se a reivindicação não existir, se o chamador não for o proprietário, ou,
+    /// numa transferência direta, se `new_owner` já tiver `MAX_CLAIMS_PER_ACCOUNT` reivindicações.
+    #[weight(10_000)]
+    pub fn transfer_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        let info = self.claims.get(&claim).ok_or(Error::ClaimNotFound)?;
+        if info.owner != caller {
+            return Err(Error::NotClaimOwner.into());
         }
 
-        self.claims.remove(&claim);
+        if new_owner == caller {
+            self.pending_transfers.remove(&claim);
+        } else if T::TWO_STEP_TRANSFERS {
+            self.pending_transfers
+                .insert(claim.clone(), new_owner.clone());
+            self.deposit_event(Event::TransferProposed {
+                from: caller,
+                to: new_owner,
+                claim,
+            });
+        } else {
+            self.do_transfer(runtime, claim, new_owner)?;
+        }
 
         Ok(())
     }
+
+    /// Aceita uma reivindicação oferecida ao `caller` com `transfer_claim`, que passa a ser o seu
+    /// proprietário.
+    /// Retornará um erro se a reivindicação não tiver sido oferecida ao chamador, ou se o chamador
+    /// já tiver `MAX_CLAIMS_PER_ACCOUNT` reivindicações.
+    #[weight(10_000)]
+    pub fn accept_claim(
+        &mut self,
+        runtime: &mut T,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        if self.pending_transfers.get(&claim) != Some(&caller) {
+            return Err(Error::NoPendingTransfer.into());
+        }
+
+        self.do_transfer(runtime, claim, caller)
+    }
 }
 
 impl<T: Config> Pallet<T> {
@@ -52,36 +327,250 @@ impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Self {
             claims: BTreeMap::new(),
+            pending_transfers: BTreeMap::new(),
+            claims_of: BTreeMap::new(),
+            expiries: BTreeSet::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Cria o pallet com as reivindicações e as transferências pendentes do bloco gênese.
+    /// Entra em pânico se o mesmo conteúdo for reivindicado mais de uma vez, já que cada
+    /// reivindicação só pode ter um proprietário, se uma descrição for longa demais, se uma conta
+    /// tiver mais de `MAX_CLAIMS_PER_ACCOUNT` reivindicações, ou se uma transferência for de uma
+    /// reivindicação que não existe.
+    pub fn from_genesis(config: GenesisConfig<T>) -> Self {
+        let mut pallet = Self::new();
+        for (claim, info) in config.claims {
+            assert!(
+                info.description.as_ref().map_or(0, Vec::len) <= T::MAX_DESCRIPTION_LENGTH,
+                "genesis claim description too long: {:?}",
+                claim
+            );
+            let owned = pallet.claims_of.entry(info.owner.clone()).or_default();
+            owned.insert(claim.clone());
+            assert!(
+                owned.len() <= T::MAX_CLAIMS_PER_ACCOUNT,
+                "too many genesis claims for {:?}",
+                info.owner
+            );
+            if let Some(expires_at) = info.expires_at {
+                pallet.expiries.insert((expires_at, claim.clone()));
+            }
+            let previous = pallet.claims.insert(claim.clone(), info);
+            assert!(previous.is_none(), "duplicate genesis claim: {:?}", claim);
+        }
+        for (claim, new_owner) in config.pending_transfers {
+            assert!(
+                pallet.claims.contains_key(&claim),
+                "genesis transfer of a missing claim: {:?}",
+                claim
+            );
+            pallet.pending_transfers.insert(claim, new_owner);
+        }
+        pallet
+    }
+
+    /// Exporta as reivindicações atuais como uma `GenesisConfig`, para iniciar uma nova cadeia a
+    /// partir deste estado.
+    pub fn genesis_config(&self) -> GenesisConfig<T> {
+        GenesisConfig {
+            claims: self.claims.clone().into_iter().collect(),
+            pending_transfers: self.pending_transfers.clone().into_iter().collect(),
         }
     }
 
     /// Obtém o proprietário (se houver) de uma reivindicação.
     pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
+        self.claims.get(claim).map(|info| &info.owner)
+    }
+
+    /// Obtém tudo o que é guardado sobre uma reivindicação: o proprietário, o depósito, o bloco em
+    /// que foi criada e a descrição.
+    pub fn claim_info(&self, claim: &T::Content) -> Option<&ClaimInfoOf<T>> {
         self.claims.get(claim)
     }
+
+    /// Obtém a conta à qual uma reivindicação foi oferecida, e que ainda não a aceitou.
+    pub fn pending_transfer(&self, claim: &T::Content) -> Option<&T::AccountId> {
+        self.pending_transfers.get(claim)
+    }
+
+    /// Obtém as reivindicações de `who`, em ordem.
+    pub fn claims_of(&self, who: &T::AccountId) -> impl Iterator<Item = &T::Content> {
+        self.claims_of.get(who).into_iter().flatten()
+    }
+
+    /// Verifica se `who` ainda pode receber mais uma reivindicação.
+    fn ensure_can_own(&self, who: &T::AccountId) -> DispatchResult {
+        if self.claims_of.get(who).map_or(0, BTreeSet::len) >= T::MAX_CLAIMS_PER_ACCOUNT {
+            return Err(Error::TooManyClaims.into());
+        }
+        Ok(())
+    }
+
+    /// Remove uma reivindicação existente junto com tudo o que depende dela, e devolve o depósito
+    /// ao proprietário.
+    fn remove_claim(&mut self, runtime: &mut T, claim: &T::Content) -> ClaimInfoOf<T> {
+        let info = self
+            .claims
+            .remove(claim)
+            .expect("only existing claims are removed; qed");
+        // O que foi cortado do depósito nesse meio tempo não é devolvido.
+        T::Currency::unreserve(runtime, &info.owner, info.deposit);
+        self.pending_transfers.remove(claim);
+        self.remove_from_index(&info.owner, claim);
+        if let Some(expires_at) = info.expires_at {
+            self.expiries.remove(&(expires_at, claim.clone()));
+        }
+        info
+    }
+
+    /// Remove `claim` das reivindicações de `owner` no índice, e remove `owner` do índice se não
+    /// sobrar nenhuma.
+    fn remove_from_index(&mut self, owner: &T::AccountId, claim: &T::Content) {
+        if let Some(owned) = self.claims_of.get_mut(owner) {
+            owned.remove(claim);
+            if owned.is_empty() {
+                self.claims_of.remove(owner);
+            }
+        }
+    }
+
+    /// Passa uma reivindicação para `new_owner`, movendo o depósito para o saldo reservado dele.
+    fn do_transfer(
+        &mut self,
+        runtime: &mut T,
+        claim: T::Content,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        self.ensure_can_own(&new_owner)?;
+        let info = self
+            .claims
+            .get_mut(&claim)
+            .expect("only existing claims are transferred; qed");
+        // O depósito só pode ficar com uma conta existente. O que foi cortado do depósito nesse
+        // meio tempo não é movido, e deixa de fazer parte do depósito.
+        let remaining = T::Currency::repatriate_reserved(
+            runtime,
+            &info.owner,
+            &new_owner,
+            info.deposit,
+            BalanceStatus::Reserved,
+        )
+        .map_err(|_| Error::NewOwnerDoesNotExist)?;
+        info.deposit = info
+            .deposit
+            .checked_sub(&remaining)
+            .ok_or(ArithmeticError::Underflow)?;
+        let from = std::mem::replace(&mut info.owner, new_owner.clone());
+        self.pending_transfers.remove(&claim);
+        self.remove_from_index(&from, &claim);
+        self.claims_of
+            .entry(new_owner.clone())
+            .or_default()
+            .insert(claim.clone());
+        self.deposit_event(Event::OwnerChanged {
+            from,
+            to: new_owner,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Registra um evento para ser coletado pelo runtime ao final da chamada.
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// Remove e retorna todos os eventos emitidos desde a última coleta.
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// A chave da `ClaimInfo` de `claim` no armazenamento deste pallet. Usada para provar o
+    /// resultado de `claim_info` contra uma raiz de estado.
+    pub fn claim_key(claim: &T::Content) -> Vec<u8> {
+        storage_key("claims", claim)
+    }
+
+    /// As entradas de armazenamento deste pallet, que o runtime usa para calcular a raiz de estado.
+    pub fn storage_entries(&self) -> Vec<StorageEntry> {
+        let claims = self
+            .claims
+            .iter()
+            .map(|(claim, value)| (Self::claim_key(claim), value.encode()));
+        let pending_transfers = self.pending_transfers.iter().map(|(claim, new_owner)| {
+            (storage_key("pending_transfers", claim), new_owner.encode())
+        });
+        let claims_of = self.claims_of.iter().map(|(owner, owned)| {
+            (
+                storage_key("claims_of", owner),
+                owned.iter().collect::<Vec<_>>().encode(),
+            )
+        });
+        let expiries = self
+            .expiries
+            .iter()
+            .map(|expiry| (storage_key("expiries", expiry), ().encode()));
+        claims
+            .chain(pending_transfers)
+            .chain(claims_of)
+            .chain(expiries)
+            .collect()
+    }
 }
 
-#[cfg(test)]
-mod test {
-    struct TestConfig;
+/// O peso de remover uma reivindicação expirada, o mesmo trabalho de `revoke_claim`. Ninguém chama
+/// `on_finalize`, então esse peso é cobrado de `create_claim` quando a reivindicação expira.
+pub const EXPIRE_CLAIM_WEIGHT: Weight = 10_000;
 
-    impl super::Config for TestConfig {
-        type Content = &'static str;
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {
+    /// Remove as reivindicações que expiram em `block_number`, devolvendo os depósitos.
+    fn on_finalize(&mut self, runtime: &mut T, block_number: T::BlockNumber) {
+        while let Some((expires_at, claim)) = self.expiries.first().cloned() {
+            if expires_at > block_number {
+                break;
+            }
+            let info = self.remove_claim(runtime, &claim);
+            self.deposit_event(Event::ClaimExpired {
+                owner: info.owner,
+                claim,
+            });
+        }
     }
+}
 
-    impl crate::system::Config for TestConfig {
-        type AccountId = &'static str;
-        type BlockNumber = u32;
-        type Nonce = u32;
+/// No modo de hashes, o runtime reivindica apenas o hash do conteúdo (`Content = Hash`). O conteúdo
+/// é resumido fora da cadeia, então os dados nunca são enviados nem guardados na cadeia.
+impl<T: Config<Content = <T as crate::system::Config>::Hash>> Pallet<T> {
+    /// O conteúdo de uma reivindicação sobre `data`: o seu hash.
+    pub fn content_of(data: &[u8]) -> T::Content {
+        T::Hashing::hash(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Error, Event};
+    use crate::balances;
+    use crate::mock::TestRuntime;
+    use crate::support::{DispatchError, Hooks};
+
+    // Um runtime onde `Alice` e `Bob` têm algum saldo.
+    fn new_runtime() -> TestRuntime {
+        TestRuntime::with_balances(vec![("Alice", 100), ("Bob", 100)])
     }
 
     #[test]
     fn basic_proof_of_existence() {
         let alice = "Alice";
-        let mut poe = super::Pallet::<TestConfig>::new();
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
         assert!(poe.get_claim(&"Hello").is_none());
 
-        poe.create_claim(alice, "Hello");
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
 
         assert_eq!(poe.get_claim(&"Hello"), Some(&alice));
     }
@@ -90,12 +579,14 @@ mod test {
     fn cant_claim_existing_claim() {
         let alice = "Alice";
         let bob = "Bob";
-        let mut poe = super::Pallet::<TestConfig>::new();
-        poe.create_claim(alice, "Hello");
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
 
         assert_eq!(
-            poe.create_claim(bob, "Hello"),
-            Err("This claim already exists")
+            poe.create_claim(&mut runtime, bob, "Hello", None, None),
+            Err(Error::ClaimAlreadyExists.into())
         );
     }
 
@@ -104,16 +595,292 @@ mod test {
     fn revoke_claim() {
         let alice = "Alice";
         let bob = "Bob";
-        let mut poe = super::Pallet::<TestConfig>::new();
-        poe.create_claim(alice, "Hello");
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, bob, "Hello"),
+            Err(Error::NotClaimOwner.into())
+        );
+        assert_eq!(poe.revoke_claim(&mut runtime, alice, "Hello"), Ok(()));
+        assert!(poe.get_claim(&"Hello").is_none());
+        assert_eq!(
+            poe.take_events(),
+            vec![
+                Event::ClaimCreated {
+                    owner: alice,
+                    claim: "Hello"
+                },
+                Event::ClaimRevoked {
+                    owner: alice,
+                    claim: "Hello"
+                },
+            ]
+        );
+
+        assert_eq!(
+            poe.revoke_claim(&mut runtime, bob, "No Claim Exists"),
+            Err(Error::ClaimNotFound.into())
+        );
+    }
+
+    #[test]
+    fn claim_deposits() {
+        let alice = "Alice";
+        let charlie = "Charlie";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+
+        // O depósito fica reservado enquanto a reivindicação existir.
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+        poe.create_claim(&mut runtime, alice, "World", None, None)
+            .unwrap();
+        assert_eq!(runtime.balances.balance(&alice), 80);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 20);
 
+        poe.revoke_claim(&mut runtime, alice, "Hello").unwrap();
+        assert_eq!(runtime.balances.balance(&alice), 90);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+        runtime.balances.assert_total_issuance();
+
+        // Contas que não podem pagar o depósito não podem reivindicar nada. O erro vem de
+        // balances, e guarda o índice de balances no runtime.
+        let error = DispatchError::from(balances::Error::InsufficientBalance);
         assert_eq!(
-            poe.revoke_claim(bob, "Hello"),
-            Err("This claim does not belong to you")
+            poe.create_claim(&mut runtime, charlie, "Hello", None, None),
+            Err(error.with_pallet_index(1))
         );
-        assert_eq!(poe.revoke_claim(alice, "Hello"), Ok(()));
         assert!(poe.get_claim(&"Hello").is_none());
+    }
+
+    #[test]
+    fn call_weights() {
+        let revoke = super::Call::<TestRuntime>::revoke_claim { claim: "Hello" };
+        assert_eq!(revoke.weight(), 10_000);
+        // O peso de uma reivindicação cresce com a sua descrição.
+        let create = |description| super::Call::<TestRuntime>::create_claim {
+            claim: "Hello",
+            description,
+            expires_at: None,
+        };
+        assert_eq!(create(None).weight(), 10_000);
+        assert_eq!(create(Some(b"Greeting".to_vec())).weight(), 10_008);
+        // A remoção de uma reivindicação que expira é paga na criação.
+        let expiring = super::Call::<TestRuntime>::create_claim {
+            claim: "Hello",
+            description: None,
+            expires_at: Some(2),
+        };
+        assert_eq!(expiring.weight(), 10_000 + super::EXPIRE_CLAIM_WEIGHT);
+    }
+
+    #[test]
+    fn claim_info() {
+        let alice = "Alice";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        runtime.system.inc_block_number();
+        runtime.system.inc_block_number();
+
+        assert_eq!(
+            poe.create_claim(
+                &mut runtime,
+                alice,
+                "Hello",
+                Some(b"Too long!".to_vec()),
+                None
+            ),
+            Err(Error::DescriptionTooLong.into())
+        );
+        poe.create_claim(
+            &mut runtime,
+            alice,
+            "Hello",
+            Some(b"Greeting".to_vec()),
+            None,
+        )
+        .unwrap();
+
+        // A reivindicação prova que o conteúdo existia no bloco em que ela foi criada.
+        assert_eq!(
+            poe.claim_info(&"Hello"),
+            Some(&super::ClaimInfo {
+                owner: alice,
+                deposit: 10,
+                block_number: 2,
+                description: Some(b"Greeting".to_vec()),
+                expires_at: None,
+            })
+        );
+        assert!(poe.claim_info(&"World").is_none());
+    }
+
+    #[test]
+    fn transfer_claim() {
+        let alice = "Alice";
+        let bob = "Bob";
+        let charlie = "Charlie";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, bob, "Hello", bob),
+            Err(Error::NotClaimOwner.into())
+        );
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, alice, "World", bob),
+            Err(Error::ClaimNotFound.into())
+        );
+        // O depósito só pode ser movido para uma conta existente.
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, alice, "Hello", charlie),
+            Err(Error::NewOwnerDoesNotExist.into())
+        );
 
-        assert!(poe.revoke_claim(bob, "No Claim Exists").is_err());
+        // Sem transferências em duas etapas, a reivindicação e o seu depósito mudam de dono
+        // imediatamente.
+        poe.transfer_claim(&mut runtime, alice, "Hello", bob)
+            .unwrap();
+        assert_eq!(poe.get_claim(&"Hello"), Some(&bob));
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(runtime.balances.balance(&alice), 90);
+        assert_eq!(runtime.balances.reserved_balance(&bob), 10);
+        assert_eq!(
+            poe.take_events().last(),
+            Some(&Event::OwnerChanged {
+                from: alice,
+                to: bob,
+                claim: "Hello"
+            })
+        );
+        assert_eq!(
+            poe.accept_claim(&mut runtime, bob, "Hello"),
+            Err(Error::NoPendingTransfer.into())
+        );
+
+        // O novo proprietário pode revogá-la, e recebe o depósito de volta.
+        poe.revoke_claim(&mut runtime, bob, "Hello").unwrap();
+        assert_eq!(runtime.balances.balance(&bob), 110);
+        runtime.balances.assert_total_issuance();
+    }
+
+    #[test]
+    fn transfer_slashed_claim() {
+        let alice = "Alice";
+        let bob = "Bob";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+
+        // Parte do depósito é cortada antes da transferência, então só o resto é movido.
+        runtime.balances.slash_reserved(&alice, 4);
+        poe.transfer_claim(&mut runtime, alice, "Hello", bob)
+            .unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(runtime.balances.reserved_balance(&bob), 6);
+        assert_eq!(poe.claim_info(&"Hello").unwrap().deposit, 6);
+
+        // Ao revogar, o novo proprietário recebe de volta só o que foi movido para ele.
+        poe.revoke_claim(&mut runtime, bob, "Hello").unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&bob), 0);
+        assert_eq!(runtime.balances.balance(&bob), 106);
+        runtime.balances.assert_total_issuance();
+    }
+
+    #[test]
+    fn claims_of() {
+        let alice = "Alice";
+        let bob = "Bob";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        assert_eq!(poe.claims_of(&alice).count(), 0);
+
+        poe.create_claim(&mut runtime, alice, "World", None, None)
+            .unwrap();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+        assert_eq!(
+            poe.claims_of(&alice).collect::<Vec<_>>(),
+            vec![&"Hello", &"World"]
+        );
+        // Nenhum depósito é reservado para uma reivindicação acima do limite.
+        assert_eq!(
+            poe.create_claim(&mut runtime, alice, "Again", None, None),
+            Err(Error::TooManyClaims.into())
+        );
+        assert_eq!(runtime.balances.reserved_balance(&alice), 20);
+
+        // O índice acompanha as reivindicações em transferências e revogações.
+        poe.transfer_claim(&mut runtime, alice, "Hello", bob)
+            .unwrap();
+        assert_eq!(poe.claims_of(&alice).collect::<Vec<_>>(), vec![&"World"]);
+        assert_eq!(poe.claims_of(&bob).collect::<Vec<_>>(), vec![&"Hello"]);
+        poe.create_claim(&mut runtime, bob, "Bye", None, None)
+            .unwrap();
+        assert_eq!(
+            poe.transfer_claim(&mut runtime, alice, "World", bob),
+            Err(Error::TooManyClaims.into())
+        );
+        assert_eq!(poe.get_claim(&"World"), Some(&alice));
+        poe.revoke_claim(&mut runtime, alice, "World").unwrap();
+        assert_eq!(poe.claims_of(&alice).count(), 0);
+        assert_eq!(
+            poe.claims_of(&bob).collect::<Vec<_>>(),
+            vec![&"Bye", &"Hello"]
+        );
+    }
+
+    #[test]
+    fn expiring_claims() {
+        let alice = "Alice";
+        let bob = "Bob";
+        let mut runtime = new_runtime();
+        let mut poe = super::Pallet::<TestRuntime>::new();
+        runtime.system.inc_block_number();
+
+        assert_eq!(
+            poe.create_claim(&mut runtime, alice, "Hello", None, Some(1)),
+            Err(Error::ExpiryInPast.into())
+        );
+        poe.create_claim(&mut runtime, alice, "Hello", None, Some(3))
+            .unwrap();
+        poe.create_claim(&mut runtime, alice, "World", None, Some(2))
+            .unwrap();
+        poe.transfer_claim(&mut runtime, alice, "World", bob)
+            .unwrap();
+        poe.take_events();
+
+        // Uma reivindicação existe até o final do bloco em que expira.
+        poe.on_finalize(&mut runtime, 1);
+        assert_eq!(poe.claims_of(&alice).count(), 1);
+        assert_eq!(poe.claims_of(&bob).count(), 1);
+        poe.on_finalize(&mut runtime, 2);
+        assert!(poe.get_claim(&"World").is_none());
+        assert_eq!(poe.claims_of(&bob).count(), 0);
+        assert_eq!(
+            poe.take_events(),
+            vec![Event::ClaimExpired {
+                owner: bob,
+                claim: "World"
+            }]
+        );
+        // Quando a reivindicação expira, o depósito volta para o seu proprietário, que é bob, já
+        // que o depósito foi transferido junto com a reivindicação.
+        assert_eq!(runtime.balances.reserved_balance(&bob), 0);
+        assert_eq!(runtime.balances.balance(&bob), 110);
+
+        // Uma reivindicação revogada não expira depois.
+        poe.revoke_claim(&mut runtime, alice, "Hello").unwrap();
+        poe.create_claim(&mut runtime, alice, "Hello", None, None)
+            .unwrap();
+        poe.on_finalize(&mut runtime, 3);
+        assert_eq!(poe.get_claim(&"Hello"), Some(&alice));
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
     }
 }
@@ -1,3 +1,6 @@
+use crate::codec::{Decode, Encode, Error as CodecError};
+use std::collections::BTreeMap;
+
 /// A representação mais primitiva de um bloco de blockchain.
 pub struct Block<Header, Extrinsic> {
     /// O cabeçalho do bloco contém metadados sobre o bloco.
@@ -6,26 +9,345 @@ pub struct Block<Header, Extrinsic> {
     pub extrinsics: Vec<Extrinsic>,
 }
 
-/// Estamos usando um cabeçalho extremamente simplificado que contém apenas o número atual do bloco.
-/// Em uma blockchain real, você esperaria encontrar também:
-/// - hash do bloco pai
-/// - raiz do estado
-/// - raiz dos extrínsecos
-/// - etc...
+impl<Header: Encode, Extrinsic: Encode> Encode for Block<Header, Extrinsic> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.header.encode_to(dest);
+        self.extrinsics.encode_to(dest);
+    }
+}
+
+impl<Header: Decode, Extrinsic: Decode> Decode for Block<Header, Extrinsic> {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Block {
+            header: Header::decode(input)?,
+            extrinsics: Vec::decode(input)?,
+        })
+    }
+}
+
+impl<Header, Extrinsic: Encode> Block<Header, Extrinsic> {
+    /// A raiz de merkle dos extrínsecos, na ordem em que aparecem no bloco. O cabeçalho deve
+    /// declarar esta raiz em `extrinsics_root`.
+    pub fn extrinsics_root(extrinsics: &[Extrinsic]) -> [u8; 32] {
+        let leaves = extrinsics.iter().map(Encode::encode).collect::<Vec<_>>();
+        crate::merkle::root(&leaves)
+    }
+}
+
+/// Estamos usando um cabeçalho simplificado. Em uma blockchain real, você esperaria encontrar
+/// também um "digest" com informações para o consenso, entre outras coisas.
+///
+/// O hash do bloco é o hash do seu cabeçalho codificado. Como o cabeçalho inclui o hash do bloco
+/// pai, cada bloco se compromete com toda a cadeia até o bloco gênese.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Header<BlockNumber> {
+    /// O hash do bloco anterior.
+    pub parent_hash: [u8; 32],
     pub block_number: BlockNumber,
+    /// A raiz do estado depois de executar este bloco.
+    pub state_root: [u8; 32],
+    /// A raiz de merkle dos extrínsecos deste bloco.
+    pub extrinsics_root: [u8; 32],
+}
+
+impl<BlockNumber: Encode> Header<BlockNumber> {
+    /// O hash deste cabeçalho, que identifica o bloco.
+    pub fn hash(&self) -> [u8; 32] {
+        crate::crypto::hash(&self.encode())
+    }
+}
+
+impl<BlockNumber: Encode> Encode for Header<BlockNumber> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.parent_hash.encode_to(dest);
+        self.block_number.encode_to(dest);
+        self.state_root.encode_to(dest);
+        self.extrinsics_root.encode_to(dest);
+    }
+}
+
+impl<BlockNumber: Decode> Decode for Header<BlockNumber> {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Header {
+            parent_hash: Decode::decode(input)?,
+            block_number: BlockNumber::decode(input)?,
+            state_root: Decode::decode(input)?,
+            extrinsics_root: Decode::decode(input)?,
+        })
+    }
 }
 
 /// Este é um "extrínseco": literalmente uma mensagem externa de fora da blockchain.
 /// Esta versão simplificada de um extrínseco nos diz quem está fazendo a chamada e qual chamada eles estão fazendo.
-pub struct Extrinsic<Caller, Call> {
+/// O `nonce` deve ser igual ao nonce atual do chamador, o que impede que o mesmo extrínseco seja
+/// executado mais de uma vez.
+///
+/// O `caller` é uma chave pública, e a `signature` deve ser a assinatura do `caller` sobre o
+/// `signing_payload` do extrínseco. Extrínsecos sem assinatura ou com uma assinatura inválida são
+/// rejeitados pelo runtime.
+///
+/// O `birth` é o número de um bloco recente da cadeia, cujo hash é assinado junto com a chamada. O
+/// extrínseco só é válido enquanto o runtime ainda guarda o hash desse bloco, e é rejeitado se a
+/// conta do `caller` foi removida depois dele, já que o nonce da conta volta a zero.
+///
+/// Além da taxa do extrínseco, o `caller` pode oferecer uma gorjeta (`tip`), que é zero quando não
+/// há gorjeta.
+#[derive(Clone)]
+pub struct Extrinsic<Caller, Call, Nonce, BlockNumber, Balance, Signature> {
     pub caller: Caller,
+    pub nonce: Nonce,
+    pub birth: BlockNumber,
     pub call: Call,
+    pub tip: Balance,
+    pub signature: Option<Signature>,
+}
+
+impl<Caller, Call, Nonce, BlockNumber, Balance, Signature>
+    Extrinsic<Caller, Call, Nonce, BlockNumber, Balance, Signature>
+where
+    Call: Encode,
+    Nonce: Encode,
+    BlockNumber: Encode,
+    Balance: Encode,
+{
+    /// Os bytes que o `caller` deve assinar: a chamada, o nonce, a gorjeta e o número e o hash do
+    /// bloco `birth`. Como o hash de um bloco depende de todos os blocos anteriores até o gênese,
+    /// ele também impede que o extrínseco seja reutilizado em outra cadeia.
+    pub fn signing_payload(&self, birth_hash: &[u8; 32]) -> Vec<u8> {
+        (&self.call, &self.nonce, &self.tip, &self.birth, birth_hash).encode()
+    }
+}
+
+impl<Caller, Call, Nonce, BlockNumber, Balance, Signature> Encode
+    for Extrinsic<Caller, Call, Nonce, BlockNumber, Balance, Signature>
+where
+    Caller: Encode,
+    Call: Encode,
+    Nonce: Encode,
+    BlockNumber: Encode,
+    Balance: Encode,
+    Signature: Encode,
+{
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.caller.encode_to(dest);
+        self.nonce.encode_to(dest);
+        self.birth.encode_to(dest);
+        self.call.encode_to(dest);
+        self.tip.encode_to(dest);
+        self.signature.encode_to(dest);
+    }
+}
+
+impl<Caller, Call, Nonce, BlockNumber, Balance, Signature> Decode
+    for Extrinsic<Caller, Call, Nonce, BlockNumber, Balance, Signature>
+where
+    Caller: Decode,
+    Call: Decode,
+    Nonce: Decode,
+    BlockNumber: Decode,
+    Balance: Decode,
+    Signature: Decode,
+{
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Extrinsic {
+            caller: Caller::decode(input)?,
+            nonce: Nonce::decode(input)?,
+            birth: BlockNumber::decode(input)?,
+            call: Call::decode(input)?,
+            tip: Balance::decode(input)?,
+            signature: Option::decode(input)?,
+        })
+    }
+}
+
+/// Uma assinatura que pode ser verificada contra a chave pública de quem assinou.
+pub trait Verify {
+    /// O tipo que identifica quem assinou, normalmente uma chave pública.
+    type Signer;
+
+    /// Retorna `true` se esta é uma assinatura válida de `signer` sobre `message`.
+    fn verify(&self, message: &[u8], signer: &Self::Signer) -> bool;
+}
+
+/// Uma função de hash, usada pelo runtime para resumir dados de qualquer tamanho em um valor de
+/// tamanho fixo.
+pub trait Hasher {
+    /// O tipo do hash.
+    type Output;
+
+    /// Calcula o hash de `data`.
+    fn hash(data: &[u8]) -> Self::Output;
 }
 
 /// O tipo Result para nosso runtime. Quando tudo é concluído com sucesso, retornamos `Ok(())`,
-/// caso contrário, retornamos uma mensagem de erro estática.
-pub type DispatchResult = Result<(), &'static str>;
+/// caso contrário, retornamos um `DispatchError` descrevendo o que deu errado.
+pub type DispatchResult = Result<(), DispatchError>;
+
+/// Uma medida do trabalho feito durante a execução de um bloco.
+pub type Weight = u64;
+
+/// O que uma chamada informa depois de executada com sucesso.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PostDispatchInfo {
+    /// O peso realmente usado pela chamada, quando ela sabe que usou menos que o peso declarado
+    /// em `#[weight(...)]`. `None` significa todo o peso declarado.
+    pub actual_weight: Option<Weight>,
+}
+
+/// Uma chamada que retorna `DispatchResult` usou todo o seu peso declarado.
+impl From<()> for PostDispatchInfo {
+    fn from(_: ()) -> Self {
+        PostDispatchInfo::default()
+    }
+}
+
+/// Como `DispatchResult`, para chamadas que podem informar o peso que realmente usaram.
+pub type DispatchResultWithPostInfo = Result<PostDispatchInfo, DispatchError>;
+
+/// O recibo de um extrínseco aplicado em um bloco.
+#[derive(Debug, Clone)]
+pub struct ExtrinsicReceipt<Balance, Event> {
+    /// O resultado da chamada. Um extrínseco rejeitado sem ser despachado falha com
+    /// `DispatchError::InvalidTransaction`.
+    pub result: DispatchResult,
+    /// O peso realmente usado pela chamada, zero se o extrínseco foi rejeitado.
+    pub weight: Weight,
+    /// A taxa paga, já descontado o reembolso e incluindo a gorjeta. Zero se o extrínseco foi
+    /// rejeitado.
+    pub fee: Balance,
+    /// Os eventos emitidos pelo extrínseco, incluindo o pagamento da taxa.
+    pub events: Vec<Event>,
+}
+
+/// O recibo de um bloco executado, com o recibo de cada um dos seus extrínsecos, na ordem em que
+/// aparecem no bloco.
+#[derive(Debug, Clone)]
+pub struct BlockReceipt<BlockNumber, Balance, Event> {
+    pub block_number: BlockNumber,
+    pub extrinsics: Vec<ExtrinsicReceipt<Balance, Event>>,
+}
+
+/// Os tipos de erro que uma chamada despachada pode retornar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchError {
+    /// Um erro definido no enum `Error` de um pallet.
+    Module(ModuleError),
+    /// Um erro aritmético, como overflow ou underflow.
+    Arithmetic(ArithmeticError),
+    /// O extrínseco é inválido e não foi despachado.
+    InvalidTransaction(InvalidTransaction),
+    /// Qualquer outro erro, descrito apenas por uma mensagem estática.
+    Other(&'static str),
+}
+
+/// Um erro definido por um pallet, gerado pela macro `#[macros::error]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleError {
+    /// A posição do pallet no `Runtime`, onde o pallet `system` tem o índice 0. `None` até o erro
+    /// sair do pallet que o criou.
+    pub index: Option<u8>,
+    /// A posição da variante no enum `Error` do pallet.
+    pub error: u8,
+    /// Uma mensagem legível descrevendo o erro.
+    pub message: &'static str,
+}
+
+/// Erros causados por operações aritméticas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticError {
+    Overflow,
+    Underflow,
+}
+
+/// Os motivos pelos quais um extrínseco pode ser rejeitado antes de ser despachado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidTransaction {
+    /// O nonce do extrínseco já foi usado.
+    Stale,
+    /// O nonce do extrínseco ainda não pode ser usado.
+    Future,
+    /// O extrínseco não foi assinado.
+    Unsigned,
+    /// A assinatura não corresponde ao chamador e ao conteúdo do extrínseco.
+    BadSignature,
+    /// O chamador não pode pagar a taxa do extrínseco.
+    Payment,
+    /// O bloco `birth` do extrínseco não é um dos blocos recentes da cadeia.
+    UnknownBirth,
+}
+
+impl DispatchError {
+    /// Registra o índice do pallet que retornou este erro, se o erro ainda não tiver um. Usado pelo
+    /// runtime ao despachar uma chamada, já que o pallet não conhece a sua própria posição no
+    /// `Runtime`. Um erro de outro pallet, repassado pela chamada, mantém o índice desse pallet.
+    pub fn with_pallet_index(self, index: u8) -> Self {
+        match self {
+            DispatchError::Module(error) => DispatchError::Module(ModuleError {
+                index: error.index.or(Some(index)),
+                ..error
+            }),
+            other => other,
+        }
+    }
+}
+
+impl From<ArithmeticError> for DispatchError {
+    fn from(error: ArithmeticError) -> Self {
+        DispatchError::Arithmetic(error)
+    }
+}
+
+impl From<InvalidTransaction> for DispatchError {
+    fn from(error: InvalidTransaction) -> Self {
+        DispatchError::InvalidTransaction(error)
+    }
+}
+
+impl From<&'static str> for DispatchError {
+    fn from(message: &'static str) -> Self {
+        DispatchError::Other(message)
+    }
+}
+
+impl core::fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ArithmeticError::Overflow => f.write_str("Overflow"),
+            ArithmeticError::Underflow => f.write_str("Underflow"),
+        }
+    }
+}
+
+impl core::fmt::Display for InvalidTransaction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InvalidTransaction::Stale => f.write_str("Stale nonce"),
+            InvalidTransaction::Future => f.write_str("Future nonce"),
+            InvalidTransaction::Unsigned => f.write_str("Unsigned extrinsic"),
+            InvalidTransaction::BadSignature => f.write_str("Bad signature"),
+            InvalidTransaction::Payment => f.write_str("Can not pay the fee"),
+            InvalidTransaction::UnknownBirth => f.write_str("Unknown birth block"),
+        }
+    }
+}
+
+impl core::fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DispatchError::Module(error) => match error.index {
+                Some(index) => write!(
+                    f,
+                    "{} (pallet {}, error {})",
+                    error.message, index, error.error
+                ),
+                None => write!(f, "{} (error {})", error.message, error.error),
+            },
+            DispatchError::Arithmetic(error) => write!(f, "Arithmetic error: {}", error),
+            DispatchError::InvalidTransaction(error) => write!(f, "Invalid transaction: {}", error),
+            DispatchError::Other(message) => f.write_str(message),
+        }
+    }
+}
 
 /// Uma trait que nos permite despachar um extrínseco recebido para a chamada de função de transição de estado apropriada.
 pub trait Dispatch {
@@ -36,5 +358,420 @@ pub trait Dispatch {
 
     /// Esta função recebe um `caller` e a `call` que eles querem fazer, e retorna um `Result`
     /// com base no resultado dessa chamada de função.
-    fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+    fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResultWithPostInfo;
+}
+
+/// Como `Dispatch`, mas para as chamadas de um pallet, gerada pela macro `#[macros::call]`.
+///
+/// Além do `caller`, recebe o runtime `R` do qual o pallet faz parte, para que as chamadas possam
+/// acessar os outros pallets através de `HasPallet`.
+pub trait PalletDispatch<R> {
+    /// O tipo usado para identificar o chamador da função.
+    type Caller;
+    /// A chamada de função de transição de estado que o chamador está tentando acessar.
+    type Call;
+
+    /// Despacha a `call` do `caller`, com acesso ao `runtime`.
+    fn dispatch(
+        &mut self,
+        runtime: &mut R,
+        caller: Self::Caller,
+        call: Self::Call,
+    ) -> DispatchResultWithPostInfo;
+}
+
+/// Um runtime que contém o pallet `P`. A macro `#[macros::runtime]` implementa esta trait para
+/// cada pallet do runtime, e é assim que um pallet acessa os outros.
+///
+/// Enquanto uma chamada de um pallet é executada, esse pallet é retirado do runtime. Então uma
+/// chamada deve acessar o seu próprio pallet através de `self`: acessá-lo através do runtime entra
+/// em pânico.
+pub trait HasPallet<P> {
+    /// A posição de `P` no runtime, onde o pallet `system` tem o índice 0. Identifica `P` nos
+    /// erros que ele retorna quando é chamado por outro pallet.
+    const INDEX: u8;
+
+    fn pallet(&self) -> &P;
+    fn pallet_mut(&mut self) -> &mut P;
+}
+
+/// Funções que o runtime chama em cada pallet durante a execução de um bloco, fora de qualquer
+/// extrínseco. A implementação padrão não faz nada. Cada gancho é chamado em todos os pallets, na
+/// ordem em que são declarados no runtime.
+///
+/// Assim como nas chamadas, o pallet é retirado do runtime `R` enquanto o gancho é executado.
+pub trait Hooks<R, BlockNumber> {
+    /// Executado no início do bloco `block_number`, antes de todos os extrínsecos. Retorna o peso
+    /// consumido.
+    fn on_initialize(&mut self, _runtime: &mut R, _block_number: BlockNumber) -> Weight {
+        0
+    }
+
+    /// Executado depois de todos os extrínsecos, com o peso que ainda sobra no bloco, para fazer
+    /// trabalho que pode esperar. Retorna o peso consumido, que não pode passar de
+    /// `remaining_weight`: o runtime entra em pânico se passar.
+    fn on_idle(&mut self, _runtime: &mut R, _remaining_weight: Weight) -> Weight {
+        0
+    }
+
+    /// Executado ao final do bloco `block_number`, depois de `on_idle`.
+    fn on_finalize(&mut self, _runtime: &mut R, _block_number: BlockNumber) {}
+}
+
+/// Cobra as taxas dos extrínsecos. O runtime usa o tipo `types::ChargeTransaction`, que cobra a
+/// taxa antes de despachar a chamada de cada extrínseco e a corrige depois, mesmo que a chamada
+/// falhe.
+pub trait ChargeTransaction<R> {
+    type AccountId;
+    type Balance;
+
+    /// Retira de `who` a taxa de um extrínseco de `length` bytes, cuja chamada tem o peso
+    /// `weight`, mais a gorjeta `tip`. Retorna o valor retirado. Se `who` não puder pagar, o
+    /// extrínseco é inválido e não é despachado.
+    fn withdraw_fee(
+        runtime: &mut R,
+        who: &Self::AccountId,
+        weight: Weight,
+        length: usize,
+        tip: Self::Balance,
+    ) -> Result<Self::Balance, DispatchError>;
+
+    /// Depois da chamada, que usou `actual_weight` do seu peso declarado `weight`, devolve a `who`
+    /// a parte de `paid` pelo peso que não foi usado. Retorna a taxa efetivamente paga.
+    fn correct_fee(
+        runtime: &mut R,
+        who: &Self::AccountId,
+        weight: Weight,
+        actual_weight: Weight,
+        paid: Self::Balance,
+        tip: Self::Balance,
+    ) -> Self::Balance;
+}
+
+/// Se uma transferência pode remover a conta da qual os fundos saem, quando ela fica com menos
+/// que o depósito existencial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistenceRequirement {
+    /// A transferência falha em vez de remover a conta.
+    KeepAlive,
+    /// A conta é removida, e o que sobrou nela é destruído.
+    AllowDeath,
+}
+
+/// Para que os fundos retirados de uma conta são usados, o que decide quais locks impedem a
+/// retirada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawReason {
+    /// Pagar a taxa ou a gorjeta de um extrínseco.
+    TransactionPayment,
+    /// Qualquer outro motivo.
+    Other,
+}
+
+/// Onde os fundos movidos de uma reserva chegam na conta do beneficiário.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceStatus {
+    /// No saldo livre, que pode ser usado imediatamente.
+    Free,
+    /// No saldo reservado.
+    Reserved,
+}
+
+/// Uma moeda, que outros pallets usam para cobrar e pagar fundos sem depender do pallet que a
+/// implementa. Um pallet recebe a moeda como um tipo associado da sua `Config`, e acessa os saldos
+/// através do runtime `R`, que uma chamada recebe com `runtime: &mut T`.
+///
+/// Todas as funções recebem o runtime, e não o pallet da moeda, já que algumas operações também
+/// precisam de outros pallets, como remover o nonce de uma conta no pallet `system`.
+pub trait Currency<R> {
+    type AccountId;
+    type Balance: num::traits::CheckedAdd
+        + num::traits::CheckedSub
+        + num::traits::Zero
+        + Copy
+        + PartialEq
+        + core::fmt::Debug
+        + Encode;
+
+    /// O menor saldo que uma conta pode ter.
+    fn minimum_balance() -> Self::Balance;
+    /// A soma de todos os saldos.
+    fn total_issuance(runtime: &R) -> Self::Balance;
+    /// O saldo livre de `who`, que pode ser transferido.
+    fn free_balance(runtime: &R, who: &Self::AccountId) -> Self::Balance;
+    /// Transfere `amount` de `from` para `to`, criando a conta de `to` se necessário.
+    fn transfer(
+        runtime: &mut R,
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        amount: Self::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+    /// Retira e destrói `amount` do saldo livre de `who`, se os locks da conta permitirem para
+    /// `reason`.
+    fn withdraw(
+        runtime: &mut R,
+        who: &Self::AccountId,
+        amount: Self::Balance,
+        reason: WithdrawReason,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+    /// Cria `amount` no saldo livre de `who`, que já deve ter uma conta.
+    fn deposit_into_existing(
+        runtime: &mut R,
+        who: &Self::AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult;
+}
+
+/// Uma moeda na qual parte do saldo livre de uma conta pode ser reservada, como um depósito, sem
+/// sair da conta.
+pub trait ReservableCurrency<R>: Currency<R> {
+    /// O saldo reservado de `who`.
+    fn reserved_balance(runtime: &R, who: &Self::AccountId) -> Self::Balance;
+    /// Move `amount` do saldo livre de `who` para o seu saldo reservado.
+    fn reserve(runtime: &mut R, who: &Self::AccountId, amount: Self::Balance) -> DispatchResult;
+    /// Devolve até `amount` do saldo reservado de `who` ao seu saldo livre. Retorna a parte de
+    /// `amount` que não estava reservada.
+    fn unreserve(runtime: &mut R, who: &Self::AccountId, amount: Self::Balance) -> Self::Balance;
+    /// Destrói até `amount` do saldo reservado de `who`. Retorna a parte de `amount` que não estava
+    /// reservada.
+    fn slash_reserved(
+        runtime: &mut R,
+        who: &Self::AccountId,
+        amount: Self::Balance,
+    ) -> Self::Balance;
+    /// Move até `amount` do saldo reservado de `slashed` para o saldo livre ou reservado de
+    /// `beneficiary`, que deve existir. Retorna a parte de `amount` que não estava reservada.
+    fn repatriate_reserved(
+        runtime: &mut R,
+        slashed: &Self::AccountId,
+        beneficiary: &Self::AccountId,
+        amount: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Uma entrada do armazenamento de um pallet: a chave e o valor, já codificados.
+pub type StorageEntry = (Vec<u8>, Vec<u8>);
+
+/// A chave de um valor no armazenamento de um pallet: o nome do item de armazenamento, seguido da
+/// chave do valor dentro desse item. Itens com um único valor usam `()` como chave.
+pub fn storage_key(item: &str, key: &impl Encode) -> Vec<u8> {
+    (item, key).encode()
+}
+
+/// A chave de uma entrada no estado do runtime: o nome do pallet no `Runtime`, seguido da chave
+/// da entrada no armazenamento do pallet. Assim, entradas de pallets diferentes nunca colidem.
+pub fn prefixed_key(pallet: &str, key: &[u8]) -> Vec<u8> {
+    [pallet.encode().as_slice(), key].concat()
+}
+
+/// Junta as entradas de armazenamento de todos os pallets em um único mapa, ordenado pela chave.
+pub fn state_entries<'a>(
+    pallets: impl IntoIterator<Item = (&'a str, Vec<StorageEntry>)>,
+) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let mut entries = BTreeMap::new();
+    for (pallet, pallet_entries) in pallets {
+        for (key, value) in pallet_entries {
+            entries.insert(prefixed_key(pallet, &key), value);
+        }
+    }
+    entries
+}
+
+/// A raiz de estado: a raiz de merkle das entradas de armazenamento, em ordem crescente de chave,
+/// onde cada folha é o par `(chave, valor)` codificado.
+///
+/// Qualquer alteração em uma chave ou valor muda a raiz, então dois nós que chegam à mesma raiz
+/// depois de executar um bloco têm exatamente o mesmo estado.
+pub fn state_root(entries: &BTreeMap<Vec<u8>, Vec<u8>>) -> [u8; 32] {
+    let leaves = entries
+        .iter()
+        .map(|entry| entry.encode())
+        .collect::<Vec<_>>();
+    crate::merkle::root(&leaves)
+}
+
+/// Uma prova de que o estado com uma certa raiz guarda `value` na chave `key`.
+///
+/// Com ela, um cliente leve que conhece apenas o cabeçalho de um bloco pode confirmar um valor do
+/// estado, como o saldo de uma conta, sem precisar do estado inteiro. Só é possível provar chaves
+/// que existem no estado: um saldo zero, por exemplo, não tem entrada no armazenamento.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub proof: crate::merkle::Proof,
+}
+
+impl StorageProof {
+    /// Gera a prova da entrada `key` de `entries`, ou `None` se a chave não existe.
+    pub fn generate(entries: &BTreeMap<Vec<u8>, Vec<u8>>, key: &[u8]) -> Option<Self> {
+        let index = entries.keys().position(|k| k.as_slice() == key)?;
+        let leaves = entries
+            .iter()
+            .map(|entry| entry.encode())
+            .collect::<Vec<_>>();
+        Some(StorageProof {
+            key: key.to_vec(),
+            value: entries[key].clone(),
+            proof: crate::merkle::prove(&leaves, index)?,
+        })
+    }
+
+    /// Verifica a prova contra a raiz de estado declarada no cabeçalho de um bloco.
+    pub fn verify(&self, state_root: &[u8; 32]) -> bool {
+        let leaf = (&self.key, &self.value).encode();
+        crate::merkle::verify(state_root, &leaf, &self.proof)
+    }
+}
+
+impl Encode for StorageProof {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.key.encode_to(dest);
+        self.value.encode_to(dest);
+        self.proof.encode_to(dest);
+    }
+}
+
+impl Decode for StorageProof {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(StorageProof {
+            key: Decode::decode(input)?,
+            value: Decode::decode(input)?,
+            proof: Decode::decode(input)?,
+        })
+    }
+}
+
+/// Executa `f` dentro de uma transação de armazenamento sobre `state`.
+///
+/// Antes de chamar `f`, tiramos uma cópia do estado. Se `f` retornar `Err`, o estado é restaurado
+/// para essa cópia, então nenhuma alteração feita por `f` permanece. Se retornar `Ok`, as
+/// alterações são mantidas.
+///
+/// Transações podem ser aninhadas: uma chamada a `with_transaction` dentro de `f` abre uma nova
+/// camada, que pode falhar e ser desfeita sem afetar a camada externa.
+///
+/// Limitação conhecida: a cópia é do estado inteiro, então o custo de cada transação cresce com o
+/// tamanho do estado, e não com o que `f` altera. O runtime abre uma transação por extrínseco,
+/// então isso só é aceitável enquanto o estado for pequeno. Um registro das alterações desfeitas
+/// (um overlay) evitaria a cópia, mas exigiria que cada pallet guardasse o seu estado por ele.
+pub fn with_transaction<State, R, E>(
+    state: &mut State,
+    f: impl FnOnce(&mut State) -> Result<R, E>,
+) -> Result<R, E>
+where
+    State: Clone,
+{
+    let snapshot = state.clone();
+    let result = f(state);
+    if result.is_err() {
+        *state = snapshot;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        state_entries, state_root, storage_key, with_transaction, ArithmeticError, DispatchError,
+        ModuleError,
+    };
+
+    #[test]
+    fn failed_transaction_rolls_back() {
+        let mut state = vec![1];
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            Err("failed")
+        });
+        assert_eq!(result, Err("failed"));
+        assert_eq!(state, vec![1]);
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(state, vec![1, 2]);
+    }
+
+    #[test]
+    fn nested_transactions() {
+        let mut state = vec![1];
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            state.push(2);
+            // A camada interna falha, mas a externa continua.
+            let inner: Result<(), &str> = with_transaction(state, |state| {
+                state.push(3);
+                Err("inner failed")
+            });
+            assert!(inner.is_err());
+            state.push(4);
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(state, vec![1, 2, 4]);
+
+        let result: Result<(), &str> = with_transaction(&mut state, |state| {
+            // A camada interna tem sucesso, mas a externa falha e desfaz tudo.
+            with_transaction(state, |state| {
+                state.push(5);
+                Ok::<(), &str>(())
+            })?;
+            Err("outer failed")
+        });
+        assert_eq!(result, Err("outer failed"));
+        assert_eq!(state, vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn dispatch_error_display() {
+        let error = DispatchError::Module(ModuleError {
+            index: None,
+            error: 2,
+            message: "Insufficient balance",
+        });
+        assert_eq!(error.to_string(), "Insufficient balance (error 2)");
+        let error = error.with_pallet_index(1);
+        assert_eq!(
+            error.to_string(),
+            "Insufficient balance (pallet 1, error 2)"
+        );
+        // O índice do pallet que criou o erro é mantido.
+        assert_eq!(error.with_pallet_index(2), error);
+
+        let error = DispatchError::from(ArithmeticError::Overflow).with_pallet_index(1);
+        assert_eq!(error, DispatchError::Arithmetic(ArithmeticError::Overflow));
+        assert_eq!(error.to_string(), "Arithmetic error: Overflow");
+
+        assert_eq!(DispatchError::from("failed").to_string(), "failed");
+    }
+
+    #[test]
+    fn state_root_commits_to_entries() {
+        let entry = |value: u32| {
+            (
+                storage_key("balances", &"alice"),
+                value.to_le_bytes().to_vec(),
+            )
+        };
+        let root = |pallet, value| state_root(&state_entries([(pallet, vec![entry(value)])]));
+
+        assert_eq!(root("balances", 1), root("balances", 1));
+        // Mudar um valor, ou movê-lo para outro pallet, muda a raiz.
+        assert_ne!(root("balances", 1), root("balances", 2));
+        assert_ne!(root("balances", 1), root("staking", 1));
+        // A ordem em que os pallets são passados não importa.
+        let a = ("a", vec![entry(1)]);
+        let b = ("b", vec![entry(2)]);
+        assert_eq!(
+            state_root(&state_entries([a.clone(), b.clone()])),
+            state_root(&state_entries([b, a]))
+        );
+    }
 }
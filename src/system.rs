@@ -1,27 +1,100 @@
+use crate::codec::Encode;
+use crate::support::{
+    storage_key, DispatchResult, Hasher, Header, InvalidTransaction, StorageEntry, Weight,
+};
+use core::fmt::Debug;
 use num::traits::{One, Zero};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub trait Config {
-    type AccountId: Ord + Clone;
-    type BlockNumber: Zero + One + Copy;
-    type Nonce: Zero + One + Copy;
+    type AccountId: Ord + Clone + Debug + Encode;
+    type BlockNumber: Zero + One + Copy + Ord + Debug + Encode;
+    type Nonce: Zero + One + Copy + Ord + Encode;
+    /// A saída de `Hashing`, usada para identificar dados pelo seu resumo.
+    type Hash: Copy + Ord + Debug + Encode;
+    type Hashing: Hasher<Output = Self::Hash>;
+    type RuntimeEvent: Debug + Clone;
+    /// Quantos dos hashes de bloco mais recentes são guardados. Deve ser pelo menos 1.
+    const BLOCK_HASH_COUNT: usize;
+    /// O peso máximo que um bloco pode usar, contando os seus hooks e as suas chamadas. Blocos mais
+    /// pesados são rejeitados.
+    const MAX_BLOCK_WEIGHT: Weight;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config> {
+    pub block_number: T::BlockNumber,
+}
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig {
+            block_number: T::BlockNumber::zero(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 
 pub struct Pallet<T: Config> {
+    genesis_hash: [u8; 32],
     block_number: T::BlockNumber,
+    block_hashes: BTreeMap<T::BlockNumber, [u8; 32]>,
     nonce: BTreeMap<T::AccountId, T::Nonce>,
+    reaped: BTreeMap<T::AccountId, T::BlockNumber>,
+    events: Vec<T::RuntimeEvent>,
+    /// Os pallets que estão fora do runtime enquanto uma das suas chamadas ou hooks executa.
+    taken_pallets: BTreeSet<&'static str>,
 }
 
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
-        Pallet {
+        let mut pallet = Pallet {
+            genesis_hash: [0; 32],
             block_number: T::BlockNumber::zero(),
+            block_hashes: BTreeMap::new(),
             nonce: BTreeMap::new(),
+            reaped: BTreeMap::new(),
+            events: Vec::new(),
+            taken_pallets: BTreeSet::new(),
+        };
+        pallet.initialize_genesis([0; 32]);
+        pallet
+    }
+
+    pub fn from_genesis(config: GenesisConfig<T>) -> Self {
+        let mut pallet = Self::new();
+        pallet.block_number = config.block_number;
+        pallet.initialize_genesis([0; 32]);
+        pallet
+    }
+
+    /// Os nonces e as contas removidas não são exportados, já que uma cadeia iniciada a partir desta
+    /// configuração tem um novo hash de gênese, então os extrínsecos assinados para esta cadeia
+    /// nunca são válidos lá.
+    pub fn genesis_config(&self) -> GenesisConfig<T> {
+        GenesisConfig {
+            block_number: self.block_number,
         }
     }
 
+    /// O bloco gênese se compromete com o estado inicial de todo o runtime, então o runtime chama
+    /// esta função de novo depois de construir todos os pallets.
+    pub fn initialize_genesis(&mut self, state_root: [u8; 32]) {
+        let genesis = Header {
+            parent_hash: [0; 32],
+            block_number: self.block_number,
+            state_root,
+            extrinsics_root: crate::merkle::root::<Vec<u8>>(&[]),
+        };
+        self.genesis_hash = genesis.hash();
+        self.block_hashes = BTreeMap::from([(self.block_number, self.genesis_hash)]);
+    }
+
+    pub fn genesis_hash(&self) -> [u8; 32] {
+        self.genesis_hash
+    }
+
     pub fn block_number(&self) -> T::BlockNumber {
         self.block_number
     }
@@ -30,23 +103,147 @@ impl<T: Config> Pallet<T> {
         self.block_number = self.block_number + T::BlockNumber::one();
     }
 
+    pub fn block_hash(&self, block_number: T::BlockNumber) -> Option<[u8; 32]> {
+        self.block_hashes.get(&block_number).copied()
+    }
+
+    pub fn last_block_hash(&self) -> [u8; 32] {
+        self.last_block().1
+    }
+
+    /// O número e o hash do último bloco importado, que novos extrínsecos podem usar como `birth`.
+    pub fn last_block(&self) -> (T::BlockNumber, [u8; 32]) {
+        let (number, hash) = self
+            .block_hashes
+            .last_key_value()
+            .expect("the genesis hash is stored on creation, and at least one hash is kept; qed");
+        (*number, *hash)
+    }
+
+    pub fn note_block_hash(&mut self, block_number: T::BlockNumber, hash: [u8; 32]) {
+        self.block_hashes.insert(block_number, hash);
+        while self.block_hashes.len() > T::BLOCK_HASH_COUNT.max(1) {
+            self.block_hashes.pop_first();
+        }
+        // Extrínsecos nascidos antes do bloco mais antigo guardado já são rejeitados, então as contas
+        // removidas até ele podem ser esquecidas.
+        if let Some((&oldest, _)) = self.block_hashes.first_key_value() {
+            self.reaped.retain(|_, reaped_at| *reaped_at > oldest);
+        }
+    }
+
+    pub fn account_nonce(&self, who: &T::AccountId) -> T::Nonce {
+        *self.nonce.get(who).unwrap_or(&T::Nonce::zero())
+    }
+
     pub fn inc_nonce(&mut self, who: &T::AccountId) {
-        let nonce = *self.nonce.get(who).unwrap_or(&T::Nonce::zero()) + T::Nonce::one();
+        let nonce = self.account_nonce(who) + T::Nonce::one();
         self.nonce.insert(who.clone(), nonce);
     }
+
+    /// Esquece a conta de `who`, o que zera o seu nonce. Chamada quando a conta é removida por
+    /// outro pallet.
+    ///
+    /// Como o nonce recomeça do zero, o bloco atual é registrado, para que os extrínsecos
+    /// assinados por `who` antes disso não voltem a ser válidos se a conta for recriada.
+    pub fn kill_account(&mut self, who: &T::AccountId) {
+        self.nonce.remove(who);
+        self.reaped.insert(who.clone(), self.block_number);
+    }
+
+    /// Verifica que a conta de `who` não foi removida depois do bloco `birth` de um extrínseco. O
+    /// nonce de um extrínseco assim pertence à conta que foi removida.
+    pub fn check_birth(&self, who: &T::AccountId, birth: T::BlockNumber) -> DispatchResult {
+        match self.reaped.get(who) {
+            Some(reaped_at) if birth < *reaped_at => Err(InvalidTransaction::Stale.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_nonce(&self, who: &T::AccountId, nonce: T::Nonce) -> DispatchResult {
+        let expected = self.account_nonce(who);
+        if nonce < expected {
+            return Err(InvalidTransaction::Stale.into());
+        }
+        if nonce > expected {
+            return Err(InvalidTransaction::Future.into());
+        }
+        Ok(())
+    }
+
+    pub fn deposit_event(&mut self, event: T::RuntimeEvent) {
+        self.events.push(event);
+    }
+
+    pub fn events(&self) -> &[T::RuntimeEvent] {
+        &self.events
+    }
+
+    pub fn reset_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Registra que o pallet `name` está fora do runtime enquanto uma das suas chamadas ou hooks
+    /// executa. Chamada pelo runtime, que deixa um pallet vazio no seu lugar.
+    pub fn take_pallet(&mut self, name: &'static str) {
+        assert!(
+            self.taken_pallets.insert(name),
+            "pallet `{}` is already taken out of the runtime",
+            name
+        );
+    }
+
+    /// Registra que o pallet `name` voltou ao runtime.
+    pub fn restore_pallet(&mut self, name: &'static str) {
+        self.taken_pallets.remove(name);
+    }
+
+    /// Entra em pânico se o pallet `name` estiver fora do runtime, já que o runtime só guarda um
+    /// pallet vazio no seu lugar. Chamada pelo runtime sempre que um pallet é acessado através de
+    /// `support::HasPallet`.
+    pub fn ensure_not_taken(&self, name: &'static str) {
+        assert!(
+            !self.taken_pallets.contains(name),
+            "pallet `{}` is taken out of the runtime while its call or hook runs, use `self` instead",
+            name
+        );
+    }
+
+    /// Os eventos não fazem parte do estado, já que são apagados no início de cada bloco, e os
+    /// pallets fora do runtime só são registrados enquanto uma chamada ou hook executa. Os hashes
+    /// de bloco também não, já que o hash de um bloco depende da sua raiz de estado.
+    pub fn storage_entries(&self) -> Vec<StorageEntry> {
+        let mut entries = vec![(storage_key("block_number", &()), self.block_number.encode())];
+        entries.extend(
+            self.nonce
+                .iter()
+                .map(|(who, nonce)| (storage_key("nonce", who), nonce.encode())),
+        );
+        entries.extend(
+            self.reaped
+                .iter()
+                .map(|(who, reaped_at)| (storage_key("reaped", who), reaped_at.encode())),
+        );
+        entries
+    }
 }
 
 #[cfg(test)]
-
 mod test {
 
     use super::Pallet;
+    use crate::support::InvalidTransaction;
     struct TestConfig;
 
     impl super::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type Hash = crate::crypto::H256;
+        type Hashing = crate::crypto::Sha256;
+        type RuntimeEvent = &'static str;
+        const BLOCK_HASH_COUNT: usize = 2;
+        const MAX_BLOCK_WEIGHT: crate::support::Weight = u64::MAX;
     }
 
     #[test]
@@ -55,14 +252,14 @@ mod test {
         let mut system = Pallet::<TestConfig>::new();
 
         assert_eq!(system.block_number(), 0);
-        assert_eq!(system.nonce.get(&"daniel".to_string()), None);
+        assert_eq!(system.nonce.get("daniel"), None);
 
         system.inc_block_number();
 
         assert_eq!(system.block_number(), 1);
 
         system.inc_nonce(&"daniel".to_string());
-        assert_eq!(system.nonce.get(&"daniel".to_string()).unwrap(), &1);
+        assert_eq!(system.nonce.get("daniel").unwrap(), &1);
     }
 
     #[test]
@@ -81,8 +278,87 @@ mod test {
         let mut system = Pallet::<TestConfig>::new();
 
         system.inc_nonce(&"daniel".to_string());
-        assert_eq!(system.nonce.get(&"daniel".to_string()).unwrap(), &1);
+        assert_eq!(system.nonce.get("daniel").unwrap(), &1);
         system.inc_nonce(&"daniel".to_string());
-        assert_eq!(system.nonce.get(&"daniel".to_string()).unwrap(), &2);
+        assert_eq!(system.nonce.get("daniel").unwrap(), &2);
+    }
+
+    #[test]
+    fn check_nonce() {
+        let mut system = Pallet::<TestConfig>::new();
+        let daniel = "daniel".to_string();
+
+        assert_eq!(system.account_nonce(&daniel), 0);
+        assert_eq!(system.check_nonce(&daniel, 0), Ok(()));
+        assert_eq!(
+            system.check_nonce(&daniel, 1),
+            Err(InvalidTransaction::Future.into())
+        );
+
+        system.inc_nonce(&daniel);
+        assert_eq!(system.account_nonce(&daniel), 1);
+        assert_eq!(
+            system.check_nonce(&daniel, 0),
+            Err(InvalidTransaction::Stale.into())
+        );
+        assert_eq!(system.check_nonce(&daniel, 1), Ok(()));
+    }
+
+    #[test]
+    fn kill_account() {
+        let mut system = Pallet::<TestConfig>::new();
+        let daniel = "daniel".to_string();
+
+        system.inc_block_number();
+        system.inc_nonce(&daniel);
+        system.kill_account(&daniel);
+        assert_eq!(system.nonce.get("daniel"), None);
+        assert_eq!(system.check_nonce(&daniel, 0), Ok(()));
+
+        // Extrínsecos nascidos antes de a conta ser removida estão obsoletos.
+        assert_eq!(
+            system.check_birth(&daniel, 0),
+            Err(InvalidTransaction::Stale.into())
+        );
+        assert_eq!(system.check_birth(&daniel, 1), Ok(()));
+        assert_eq!(system.check_birth(&"alice".to_string(), 0), Ok(()));
+
+        // Quando os blocos anteriores deixam de ser guardados, a conta é esquecida.
+        system.note_block_hash(1, [1; 32]);
+        assert_eq!(system.reaped.get("daniel"), Some(&1));
+        system.note_block_hash(2, [2; 32]);
+        assert_eq!(system.reaped.get("daniel"), None);
+    }
+
+    #[test]
+    fn deposit_events() {
+        let mut system = Pallet::<TestConfig>::new();
+        assert!(system.events().is_empty());
+
+        system.deposit_event("first");
+        system.deposit_event("second");
+        assert_eq!(system.events(), &["first", "second"]);
+
+        system.reset_events();
+        assert!(system.events().is_empty());
+    }
+
+    #[test]
+    fn block_hashes() {
+        let mut system = Pallet::<TestConfig>::new();
+        let genesis_hash = system.genesis_hash();
+        assert_eq!(system.block_hash(0), Some(genesis_hash));
+        assert_eq!(system.last_block_hash(), genesis_hash);
+
+        system.note_block_hash(1, [1; 32]);
+        assert_eq!(system.last_block_hash(), [1; 32]);
+        assert_eq!(system.block_hash(0), Some(genesis_hash));
+
+        // Só os dois hashes mais recentes são guardados.
+        system.note_block_hash(2, [2; 32]);
+        assert_eq!(system.last_block_hash(), [2; 32]);
+        assert_eq!(system.block_hash(1), Some([1; 32]));
+        assert_eq!(system.block_hash(0), None);
+        assert_eq!(system.genesis_hash(), genesis_hash);
     }
 }
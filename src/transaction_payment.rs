@@ -0,0 +1,231 @@
+use crate::support::{
+    ChargeTransaction, Currency, DispatchError, ExistenceRequirement, HasPallet, Hooks,
+    InvalidTransaction, StorageEntry, Weight, WithdrawReason,
+};
+use core::marker::PhantomData;
+use num::traits::{CheckedAdd, CheckedSub, Zero};
+
+pub trait Config: crate::system::Config + Sized {
+    /// A moeda com a qual as taxas são pagas, acessada através do runtime.
+    type Currency: Currency<Self, AccountId = Self::AccountId>;
+
+    /// A taxa cobrada pelo peso `weight` da chamada de um extrínseco.
+    fn weight_to_fee(weight: Weight) -> BalanceOf<Self>;
+    /// A taxa cobrada por um extrínseco de `length` bytes, o espaço que ele ocupa no bloco.
+    fn length_to_fee(length: usize) -> BalanceOf<Self>;
+}
+
+/// O tipo de saldo da moeda usada para pagar as taxas.
+pub type BalanceOf<T> = <<T as Config>::Currency as Currency<T>>::Balance;
+
+/// Os eventos emitidos por este pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// `who` pagou `actual_fee` por um extrínseco, já descontado o reembolso e incluindo a
+    /// gorjeta `tip`.
+    TransactionFeePaid {
+        who: T::AccountId,
+        actual_fee: BalanceOf<T>,
+        tip: BalanceOf<T>,
+    },
+}
+
+/// O estado inicial deste pallet, que não guarda nada.
+#[derive(Debug, Clone)]
+pub struct GenesisConfig<T: Config>(PhantomData<T>);
+
+impl<T: Config> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        GenesisConfig(PhantomData)
+    }
+}
+
+/// Este é o Módulo de Pagamento de Transações.
+/// Cobra de cada extrínseco uma taxa pelo peso da sua chamada e pelo seu tamanho, mais uma gorjeta
+/// opcional. A taxa é destruída, já que não há um autor de bloco para recebê-la.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pallet<T: Config> {
+    /// Eventos emitidos e ainda não coletados pelo runtime.
+    events: Vec<Event<T>>,
+}
+
+// Este pallet não tem chamadas, mas o runtime precisa do seu `Call`.
+#[macros::call]
+impl<T: Config> Pallet<T> {}
+
+impl<T: Config> Pallet<T> {
+    /// Cria uma nova instância do Módulo de Pagamento de Transações.
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Cria o pallet a partir do bloco gênese.
+    pub fn from_genesis(_config: GenesisConfig<T>) -> Self {
+        Self::new()
+    }
+
+    /// Exporta o estado atual como uma `GenesisConfig`.
+    pub fn genesis_config(&self) -> GenesisConfig<T> {
+        GenesisConfig::default()
+    }
+
+    /// A taxa de um extrínseco de `length` bytes, cuja chamada tem o peso `weight`, com a gorjeta
+    /// `tip`. Retorna `None` se a taxa não couber no tipo de saldo.
+    pub fn compute_fee(weight: Weight, length: usize, tip: BalanceOf<T>) -> Option<BalanceOf<T>> {
+        T::weight_to_fee(weight)
+            .checked_add(&T::length_to_fee(length))?
+            .checked_add(&tip)
+    }
+
+    /// Registra um evento para ser coletado pelo runtime ao final do extrínseco.
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    /// Remove e retorna todos os eventos emitidos desde a última coleta.
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// As entradas de armazenamento deste pallet, que não guarda nada.
+    pub fn storage_entries(&self) -> Vec<StorageEntry> {
+        Vec::new()
+    }
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config + HasPallet<Pallet<T>>> ChargeTransaction<T> for Pallet<T> {
+    type AccountId = T::AccountId;
+    type Balance = BalanceOf<T>;
+
+    /// A taxa nunca remove a conta de quem paga, e não pode ser paga com fundos bloqueados para
+    /// taxas.
+    fn withdraw_fee(
+        runtime: &mut T,
+        who: &T::AccountId,
+        weight: Weight,
+        length: usize,
+        tip: BalanceOf<T>,
+    ) -> Result<BalanceOf<T>, DispatchError> {
+        let fee = Self::compute_fee(weight, length, tip).ok_or(InvalidTransaction::Payment)?;
+        // O erro da moeda não é o resultado de uma chamada: quem não pode pagar tem o extrínseco
+        // rejeitado, como descrito em `ChargeTransaction::withdraw_fee`.
+        T::Currency::withdraw(
+            runtime,
+            who,
+            fee,
+            WithdrawReason::TransactionPayment,
+            ExistenceRequirement::KeepAlive,
+        )
+        .map_err(|_| InvalidTransaction::Payment)?;
+        Ok(fee)
+    }
+
+    /// O reembolso é perdido se a chamada removeu a conta de `who`.
+    fn correct_fee(
+        runtime: &mut T,
+        who: &T::AccountId,
+        weight: Weight,
+        actual_weight: Weight,
+        paid: BalanceOf<T>,
+        tip: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        let refund = T::weight_to_fee(weight)
+            .checked_sub(&T::weight_to_fee(actual_weight))
+            .unwrap_or_else(Zero::zero);
+        let refunded =
+            !refund.is_zero() && T::Currency::deposit_into_existing(runtime, who, refund).is_ok();
+        let actual_fee = match refunded {
+            true => paid.checked_sub(&refund).unwrap_or_else(Zero::zero),
+            false => paid,
+        };
+        let pallet: &mut Pallet<T> = runtime.pallet_mut();
+        pallet.deposit_event(Event::TransactionFeePaid {
+            who: who.clone(),
+            actual_fee,
+            tip,
+        });
+        actual_fee
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Event;
+    use crate::balances;
+    use crate::mock::TestRuntime;
+    use crate::support::{ChargeTransaction, DispatchError, InvalidTransaction};
+
+    type Payment = super::Pallet<TestRuntime>;
+
+    #[test]
+    fn fees() {
+        let alice = "alice";
+        let mut runtime = TestRuntime::with_balances(vec![(alice, 100)]);
+
+        // A taxa é a taxa do peso, mais a taxa do tamanho, mais a gorjeta.
+        assert_eq!(Payment::compute_fee(20_000, 5, 3), Some(28));
+        assert_eq!(Payment::compute_fee(1_000, 0, u128::MAX), None);
+
+        assert_eq!(
+            Payment::withdraw_fee(&mut runtime, &alice, 20_000, 5, 3),
+            Ok(28)
+        );
+        assert_eq!(runtime.balances.balance(&alice), 72);
+        assert_eq!(runtime.balances.total_issuance(), 72);
+
+        // A chamada usou metade do seu peso, então metade da taxa do peso é devolvida.
+        assert_eq!(
+            Payment::correct_fee(&mut runtime, &alice, 20_000, 10_000, 28, 3),
+            18
+        );
+        assert_eq!(runtime.balances.balance(&alice), 82);
+        assert_eq!(
+            runtime.transaction_payment.take_events(),
+            vec![Event::TransactionFeePaid {
+                who: alice,
+                actual_fee: 18,
+                tip: 3
+            }]
+        );
+        runtime.balances.assert_total_issuance();
+    }
+
+    #[test]
+    fn cant_pay_fees() {
+        let alice = "alice";
+        let bob = "bob";
+        let mut runtime = TestRuntime::with_balances(vec![(alice, 100)]);
+        let payment = Err(DispatchError::InvalidTransaction(
+            InvalidTransaction::Payment,
+        ));
+
+        // Pagar uma taxa nunca remove a conta.
+        assert_eq!(
+            Payment::withdraw_fee(&mut runtime, &bob, 1_000, 0, 0),
+            payment
+        );
+        assert_eq!(
+            Payment::withdraw_fee(&mut runtime, &alice, 0, 99, 0),
+            payment
+        );
+
+        // Fundos bloqueados para taxas não podem pagá-las.
+        let id = *b"fees    ";
+        runtime
+            .balances
+            .set_lock(id, &alice, 50, balances::Reasons::Fee)
+            .unwrap();
+        assert_eq!(
+            Payment::withdraw_fee(&mut runtime, &alice, 0, 51, 0),
+            payment
+        );
+        assert_eq!(
+            Payment::withdraw_fee(&mut runtime, &alice, 0, 50, 0),
+            Ok(50)
+        );
+        assert_eq!(runtime.balances.balance(&alice), 50);
+        assert!(runtime.transaction_payment.take_events().is_empty());
+    }
+}